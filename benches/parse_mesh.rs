@@ -0,0 +1,328 @@
+// Compare `cargo bench` with `cargo bench --features parallel` to see
+// the speedup of parallel parsing.
+// The `parse_faces` group compares the nested and the flat (CSR) face
+// layouts on a mesh with about three million faces.
+// The `parse_faces_scanner` group compares `parse_faces`, which scans
+// bytes, with the former regex-based parser on about a million faces.
+// The `parse_points` group parses a large file of points with realistic
+// coordinates; compare it with `--features fast-float`.
+// The `parse_internal_field` group reports the peak memory (RSS, on Linux)
+// of parsing a large vector field, which is read line by line.
+
+extern crate criterion;
+extern crate nalgebra;
+extern crate openfoamparser;
+extern crate regex;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use nalgebra::Vector3;
+use openfoamparser::{parse_internal_field, parse_vector3, FlatFaces, FoamMesh};
+use regex::Regex;
+
+/// Number of cells along each axis of the synthetic mesh.
+const N: usize = 60;
+
+/// Number of cells along each axis of the mesh for the `parse_faces` group.
+const N_FACES: usize = 100;
+
+/// Number of cells along each axis of the mesh for the
+/// `parse_faces_scanner` group, giving about a million faces.
+const N_SCANNER: usize = 70;
+
+/// Number of points for the `parse_points` group.
+const N_POINTS: usize = 2_000_000;
+
+/// Number of values of the field for the `parse_internal_field` group.
+const N_VALUES: usize = 2_000_000;
+
+const HEADER: &str = "FoamFile\n{\n    version     2.0;\n    \
+    format      ascii;\n    class       dummy;\n    object      dummy;\n}\n";
+
+fn write_list(path: &Path, values: &[String]) {
+    let mut s = String::from(HEADER);
+    writeln!(s, "\n{}\n(", values.len()).unwrap();
+    for v in values {
+        s.push_str(v);
+        s.push('\n');
+    }
+    s.push_str(")\n");
+    fs::write(path, s).unwrap();
+}
+
+/// Write a cube of `n`^3 hexahedral cells with a single boundary patch.
+fn write_synthetic_mesh(n: usize) -> PathBuf {
+    let case = std::env::temp_dir().join(
+        format!("openfoamparser_bench_{}", n));
+    let mesh_dir = case.join("constant/polyMesh");
+    fs::create_dir_all(&mesh_dir).unwrap();
+
+    let np = n + 1;
+    let point = |i: usize, j: usize, k: usize| i + np * (j + np * k);
+    let cell = |i: usize, j: usize, k: usize| i + n * (j + n * k);
+
+    let mut points: Vec<String> = Vec::new();
+    for k in 0..np {
+        for j in 0..np {
+            for i in 0..np {
+                points.push(format!("({} {} {})", i, j, k));
+            }
+        }
+    }
+
+    // Faces normal to x, y, and z at grid position (i, j, k):
+    let x_face = |i, j, k| [
+        point(i, j, k), point(i, j + 1, k),
+        point(i, j + 1, k + 1), point(i, j, k + 1)
+    ];
+    let y_face = |i, j, k| [
+        point(i, j, k), point(i, j, k + 1),
+        point(i + 1, j, k + 1), point(i + 1, j, k)
+    ];
+    let z_face = |i, j, k| [
+        point(i, j, k), point(i + 1, j, k),
+        point(i + 1, j + 1, k), point(i, j + 1, k)
+    ];
+    let fmt_face = |f: [usize; 4]| {
+        format!("4({} {} {} {})", f[0], f[1], f[2], f[3])
+    };
+
+    let mut faces: Vec<String> = Vec::new();
+    let mut owners: Vec<String> = Vec::new();
+    let mut neighbours: Vec<String> = Vec::new();
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                let c = cell(i, j, k);
+                if i + 1 < n {
+                    faces.push(fmt_face(x_face(i + 1, j, k)));
+                    owners.push(c.to_string());
+                    neighbours.push(cell(i + 1, j, k).to_string());
+                }
+                if j + 1 < n {
+                    faces.push(fmt_face(y_face(i, j + 1, k)));
+                    owners.push(c.to_string());
+                    neighbours.push(cell(i, j + 1, k).to_string());
+                }
+                if k + 1 < n {
+                    faces.push(fmt_face(z_face(i, j, k + 1)));
+                    owners.push(c.to_string());
+                    neighbours.push(cell(i, j, k + 1).to_string());
+                }
+            }
+        }
+    }
+    let num_inner_faces = faces.len();
+    for a in 0..n {
+        for b in 0..n {
+            for &(face, owner) in &[
+                (x_face(0, a, b), cell(0, a, b)),
+                (x_face(n, a, b), cell(n - 1, a, b)),
+                (y_face(a, 0, b), cell(a, 0, b)),
+                (y_face(a, n, b), cell(a, n - 1, b)),
+                (z_face(a, b, 0), cell(a, b, 0)),
+                (z_face(a, b, n), cell(a, b, n - 1)),
+            ] {
+                faces.push(fmt_face(face));
+                owners.push(owner.to_string());
+            }
+        }
+    }
+
+    write_list(&mesh_dir.join("points"), &points);
+    write_list(&mesh_dir.join("faces"), &faces);
+    write_list(&mesh_dir.join("owner"), &owners);
+    write_list(&mesh_dir.join("neighbour"), &neighbours);
+    fs::write(mesh_dir.join("boundary"), format!(
+        "{}\n1\n(\n    walls\n    {{\n        type            wall;\n        \
+        nFaces          {};\n        startFace       {};\n    }}\n)\n",
+        HEADER,
+        faces.len() - num_inner_faces,
+        num_inner_faces
+    )).unwrap();
+    case
+}
+
+/// Write a vector field with `n` values without holding it in memory.
+fn write_synthetic_field(n: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(
+        format!("openfoamparser_bench_U_{}", n));
+    let mut w = BufWriter::new(fs::File::create(&path).unwrap());
+    write!(w, "{}\ndimensions      [0 1 -1 0 0 0 0];\n\n\
+        internalField   nonuniform List<vector>\n{}\n(\n", HEADER, n).unwrap();
+    for i in 0..n {
+        writeln!(w, "({} {} -0.000123456)", i as f64 * 1e-6, 0.5).unwrap();
+    }
+    writeln!(w, ")\n;\n\nboundaryField\n{{\n}}").unwrap();
+    w.flush().unwrap();
+    path
+}
+
+/// Write a points file with `n` points whose coordinates have as many
+/// digits as those written by OpenFOAM.
+fn write_synthetic_points(n: usize) -> PathBuf {
+    let path = std::env::temp_dir().join(
+        format!("openfoamparser_bench_points_{}", n));
+    let mut w = BufWriter::new(fs::File::create(&path).unwrap());
+    write!(w, "{}\n{}\n(\n", HEADER, n).unwrap();
+    for i in 0..n {
+        let t = i as f64 / n as f64;
+        writeln!(w, "({} {} {:e})", t * 0.1, 0.05 - t * 1.234, -t * 1e-3)
+            .unwrap();
+    }
+    writeln!(w, ")").unwrap();
+    w.flush().unwrap();
+    path
+}
+
+/// Peak resident set size of this process in MiB (only on Linux).
+fn peak_rss_mib() -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kib: usize = status.lines()
+        .find(|line| line.starts_with("VmHWM:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some(kib >> 10)
+}
+
+fn bench_parse_internal_field(c: &mut Criterion) {
+    let path = write_synthetic_field(N_VALUES);
+    // This group runs first, so the peak is not yet raised by the others:
+    let rss_before = peak_rss_mib();
+    let field: Vec<Vector3<f64>> =
+        parse_internal_field(&path, parse_vector3::<f64>).unwrap();
+    if let (Some(before), Some(after)) = (rss_before, peak_rss_mib()) {
+        println!(
+            "{} values ({} MiB file, {} MiB parsed): peak RSS {} -> {} MiB",
+            field.len(),
+            fs::metadata(&path).unwrap().len() >> 20,
+            (field.capacity() * std::mem::size_of::<Vector3<f64>>()) >> 20,
+            before,
+            after
+        );
+    }
+    drop(field);
+
+    let mut group = c.benchmark_group("parse_internal_field");
+    group.sample_size(10);
+    group.bench_function("parse_internal_field", |b| {
+        b.iter(|| parse_internal_field(&path, parse_vector3::<f64>).unwrap())
+    });
+    group.finish();
+    fs::remove_file(&path).unwrap();
+}
+
+fn bench_new_mesh(c: &mut Criterion) {
+    let case = write_synthetic_mesh(N);
+    let mut group = c.benchmark_group("parse_mesh");
+    group.sample_size(10);
+    group.bench_function("FoamMesh::new", |b| {
+        b.iter(|| FoamMesh::new(&case).unwrap())
+    });
+    group.finish();
+    fs::remove_dir_all(&case).unwrap();
+}
+
+/// Heap memory of the nested face layout.
+fn nested_bytes(faces: &[Vec<usize>]) -> usize {
+    std::mem::size_of_val(faces)
+        + faces.iter()
+            .map(|f| f.capacity() * std::mem::size_of::<usize>())
+            .sum::<usize>()
+}
+
+/// Heap memory of the flat face layout.
+fn flat_bytes(faces: &FlatFaces) -> usize {
+    faces.offsets.capacity() * std::mem::size_of::<usize>()
+        + faces.data.capacity() * std::mem::size_of::<u32>()
+}
+
+fn bench_parse_faces(c: &mut Criterion) {
+    let case = write_synthetic_mesh(N_FACES);
+    let faces_file = case.join("constant/polyMesh/faces");
+    let nested = FoamMesh::parse_faces(&faces_file, None).unwrap();
+    let flat = FoamMesh::parse_faces_flat(&faces_file, None).unwrap();
+    println!(
+        "{} faces: {} MiB nested, {} MiB flat",
+        flat.len(),
+        nested_bytes(&nested) >> 20,
+        flat_bytes(&flat) >> 20
+    );
+    drop(nested);
+
+    let mut group = c.benchmark_group("parse_faces");
+    group.sample_size(10);
+    group.bench_function("FoamMesh::parse_faces", |b| {
+        b.iter(|| FoamMesh::parse_faces(&faces_file, None).unwrap())
+    });
+    group.bench_function("FoamMesh::parse_faces_flat", |b| {
+        b.iter(|| FoamMesh::parse_faces_flat(&faces_file, None).unwrap())
+    });
+    group.finish();
+    fs::remove_dir_all(&case).unwrap();
+}
+
+/// Parse a face list with a `\d+` regex per line, as `parse_faces` did
+/// before it scanned bytes. Assumes a well-formed list with one face per
+/// line.
+fn parse_faces_regex(path: &Path) -> Vec<Vec<usize>> {
+    let re = Regex::new(r"\d+").unwrap();
+    let content = fs::read_to_string(path).unwrap();
+    let mut lines = content.lines()
+        .skip_while(|line| line.trim() != "(");
+    lines.next();
+    lines.take_while(|line| line.trim() != ")")
+        .map(|line| re.find_iter(line)
+            .skip(1)
+            .map(|num| num.as_str().parse().unwrap())
+            .collect())
+        .collect()
+}
+
+fn bench_parse_faces_scanner(c: &mut Criterion) {
+    let case = write_synthetic_mesh(N_SCANNER);
+    let faces_file = case.join("constant/polyMesh/faces");
+    assert_eq!(
+        parse_faces_regex(&faces_file),
+        FoamMesh::parse_faces(&faces_file, None).unwrap()
+    );
+
+    let mut group = c.benchmark_group("parse_faces_scanner");
+    group.sample_size(10);
+    group.bench_function("regex", |b| {
+        b.iter(|| parse_faces_regex(&faces_file))
+    });
+    group.bench_function("FoamMesh::parse_faces", |b| {
+        b.iter(|| FoamMesh::parse_faces(&faces_file, None).unwrap())
+    });
+    group.finish();
+    fs::remove_dir_all(&case).unwrap();
+}
+
+fn bench_parse_points(c: &mut Criterion) {
+    let path = write_synthetic_points(N_POINTS);
+    let mut group = c.benchmark_group("parse_points");
+    group.sample_size(10);
+    group.bench_function("FoamMesh::parse_points", |b| {
+        b.iter(|| FoamMesh::parse_points(&path, None).unwrap())
+    });
+    group.finish();
+    fs::remove_file(&path).unwrap();
+}
+
+criterion_group!(
+    benches,
+    bench_parse_internal_field,
+    bench_new_mesh,
+    bench_parse_faces,
+    bench_parse_faces_scanner,
+    bench_parse_points
+);
+criterion_main!(benches);
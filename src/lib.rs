@@ -22,7 +22,22 @@
 //! like the Python library [openfoamparser](https://github.com/ApolloLV/openfoamparser.git).
 //!
 //! Known limitations:
-//! - Parsing binary files is not supported yet.
+//! - Parsing binary files is only supported for internal fields, not yet
+//!   for mesh files.
+//!
+//! Optional features:
+//! - `serde`: Implements `Serialize` and `Deserialize` for `FoamMesh`,
+//!   e.g., for caching parsed meshes.
+//! - `parallel`: Parses mesh files concurrently using rayon.
+//! - `u32-index`: Stores the point, face, and cell indices of `FoamMesh`
+//!   as `u32` instead of `usize` (see `Index`), which halves the memory
+//!   of the connectivity on 64-bit platforms.
+//! - `kdtree`: Adds `FoamMesh::build_cell_index` to speed up
+//!   `FoamMesh::nearest_cell` with a KD-tree.
+//! - `mmap`: Adds `FoamMesh::new_mmap`, which memory-maps the mesh files
+//!   instead of reading them.
+//! - `fast-float`: Parses the floats of points and vectors (e.g., with
+//!   `parse_point3`) with a faster parser than the standard library's.
 //!
 //! # Getting Started
 //!
@@ -59,6 +74,18 @@
 
 extern crate nalgebra as na;
 extern crate regex;
+extern crate flate2;
+extern crate thiserror;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "kdtree")]
+extern crate kdtree;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "fast-float")]
+extern crate fast_float2;
 
 #[macro_use]
 extern crate lazy_static;
@@ -66,89 +93,507 @@ extern crate lazy_static;
 extern crate approx;
 
 use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
-use na::{geometry::Point3, Vector3};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use na::{geometry::{Point2, Point3}, DMatrix, DVector, Matrix3};
+use na::{Vector2, Vector3};
 use regex::Regex;
+use flate2::read::GzDecoder;
+use thiserror::Error;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "kdtree")]
+use kdtree::{distance::squared_euclidean, KdTree};
+
+/// Type of the point, face, and cell indices stored in `FoamMesh`.
+///
+/// This is `u32` with the `u32-index` feature, and `usize` otherwise.
+#[cfg(not(feature = "u32-index"))]
+pub type Index = usize;
+#[cfg(feature = "u32-index")]
+pub type Index = u32;
+
+/// Convert an `Index` to `usize`, e.g., for indexing.
+#[allow(clippy::unnecessary_cast)]
+#[inline]
+fn idx(i: Index) -> usize {
+    i as usize
+}
+
+/// Convert a `usize` to an `Index`, failing if it exceeds `Index::MAX`.
+#[allow(clippy::useless_conversion)]
+fn to_index(i: usize) -> Result<Index, FoamError> {
+    Index::try_from(i).map_err(|_| FoamError::InvalidData(format!(
+        "Index {} exceeds the range of the index type ({}).",
+        i,
+        std::any::type_name::<Index>()
+    )))
+}
+
+/// Errors that can occur while parsing OpenFOAM files.
+#[derive(Debug, Error)]
+pub enum FoamError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("{expected} values expected, but parsed {found}.")]
+    CountMismatch { expected: usize, found: usize },
+    #[error(
+        "Malformed line {line} of \"{}\" (\"{content}\"): {reason}",
+        .file.to_string_lossy()
+    )]
+    MalformedLine {
+        file: PathBuf,
+        /// Line number, starting at 1.
+        line: usize,
+        content: String,
+        reason: String,
+    },
+    #[error("Missing '{bracket}' {context}.")]
+    MissingBracket { bracket: char, context: String },
+    #[error("{0}")]
+    InvalidData(String),
+}
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FoamMesh {
     pub path: PathBuf,
+    /// Name of the mesh region (empty for the default region).
+    pub region: String,
     pub boundary: HashMap<String, Boundary>,
     pub points: Vec<Point3<f64>>,
     /// A face is defined as a list of point indices.
     /// Each face also is represented in the list of
     /// owners and neighbors.
-    pub faces: Vec<Vec<usize>>,
-    pub cell_faces: Vec<Vec<usize>>,
+    pub faces: Vec<Vec<Index>>,
+    pub cell_faces: Vec<Vec<Index>>,
     /// Indices of the cell that is the owner of the
     /// respective face.
-    pub owners: Vec<usize>,
+    pub owners: Vec<Index>,
     /// Indices of neighboring cells for each internal
     /// face.
     pub neighbors: Vec<i64>,
     pub cell_neighbors: Vec<Vec<i64>>,
     pub cell_centers: Option<Vec<Point3<f64>>>,
+    /// Cell indices of each cell zone (empty if there is no `cellZones`
+    /// file).
+    pub cell_zones: HashMap<String, Vec<usize>>,
+    /// Face zones (empty if there is no `faceZones` file).
+    pub face_zones: HashMap<String, FaceZone>,
+    /// Point indices of each point zone (empty if there is no `pointZones`
+    /// file).
+    pub point_zones: HashMap<String, Vec<usize>>,
     num_inner_faces: usize,
     num_cells: usize,
+    /// KD-tree over the cell centers, see `build_cell_index`.
+    #[cfg(feature = "kdtree")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cell_index: Option<KdTree<f64, usize, [f64; 3]>>,
     // pub cell_volumes: ???,
     // pub face_areas: ???
 }
 
-#[derive(Debug)]
+/// Formats the mesh sizes in one line, e.g., "3200 cells, 11360 faces
+/// (7840 internal), 5043 points".
+impl std::fmt::Display for FoamMesh {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} cells, {} faces ({} internal), {} points",
+            self.num_cells(),
+            self.num_faces(),
+            self.num_inner_faces(),
+            self.num_points()
+        )
+    }
+}
+
+/// A face zone as defined in `constant/polyMesh/faceZones`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FaceZone {
+    pub faces: Vec<usize>,
+    /// Whether the respective face's orientation is flipped with respect
+    /// to the zone.
+    pub flip_map: Vec<bool>,
+}
+
+/// The kind of a set in `constant/polyMesh/sets/`, see
+/// `FoamMesh::load_sets`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SetKind {
+    /// A `cellSet` of cell indices.
+    Cell,
+    /// A `faceSet` of face indices.
+    Face,
+    /// A `pointSet` of point indices.
+    Point,
+}
+
+impl SetKind {
+    /// Get the kind of set from the class in the `FoamFile` header, e.g.,
+    /// `cellSet`, or `None` for any other class.
+    pub fn from_class(class: &str) -> Option<SetKind> {
+        match class {
+            "cellSet" => Some(SetKind::Cell),
+            "faceSet" => Some(SetKind::Face),
+            "pointSet" => Some(SetKind::Point),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Boundary {
     pub boundary_type: String,
     pub num_faces: usize,
     pub start_face: usize,
     pub boundary_id: i64,  // original implementation seems to allow neg. values
+    /// Names of the patch groups given by `inGroups`, e.g., `wall`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub in_groups: Vec<String>,
+    /// Name of the coupled patch given by `neighbourPatch`, e.g., of a
+    /// `cyclic` patch.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub neighbour_patch: Option<String>,
 }
 
-impl FoamMesh {
-    pub fn new<P: AsRef<Path>>(path: P) -> Result<FoamMesh, io::Error> {
-        let mut pb: PathBuf = PathBuf::new();
-        pb.push(&path);
-        pb.push("constant/polyMesh/");
-
-        let boundary: HashMap<String, Boundary> = FoamMesh::parse_boundary(
-            &pb.join("boundary"), 10)?;
-        let faces: Vec<Vec<usize>> = FoamMesh::parse_faces(&pb.join("faces"), 10)?;
-        let owners: Vec<usize> = FoamMesh::parse_scalars(&pb.join("owner"), 10)?;
-        let mut neighbors: Vec<i64> = FoamMesh::parse_scalars(
-            &pb.join("neighbour"), 10)?; // OpenFoam uses the British spelling
+impl Boundary {
+    /// Get the patch type parsed from `boundary_type`.
+    pub fn kind(&self) -> PatchType {
+        PatchType::from(self.boundary_type.as_str())
+    }
+}
 
-        let num_faces = owners.len();
-        let num_inner_faces = neighbors.len();
-        let num_cells: usize = *owners.iter().max().unwrap();
+/// The type of a boundary patch, see `Boundary::kind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchType {
+    Patch,
+    Wall,
+    Empty,
+    Symmetry,
+    SymmetryPlane,
+    Wedge,
+    Cyclic,
+    CyclicAMI,
+    Processor,
+    /// Any other type, e.g., `mappedWall`.
+    Other(String),
+}
 
-        // _set_boundary_faces:
-        neighbors.extend(vec![-10; num_faces - num_inner_faces]);
-        for b in boundary.values() {
-            for i in b.start_face .. b.start_face + b.num_faces {
-                neighbors[i] = b.boundary_id;
-            }
+impl PatchType {
+    /// Check whether the patch is coupled to another patch, such as a
+    /// `cyclic` patch to its `neighbour_patch`.
+    pub fn is_coupled(&self) -> bool {
+        matches!(self,
+            PatchType::Cyclic | PatchType::CyclicAMI | PatchType::Processor)
+    }
+}
+
+impl From<&str> for PatchType {
+    fn from(s: &str) -> PatchType {
+        match s {
+            "patch" => PatchType::Patch,
+            "wall" => PatchType::Wall,
+            "empty" => PatchType::Empty,
+            "symmetry" => PatchType::Symmetry,
+            "symmetryPlane" => PatchType::SymmetryPlane,
+            "wedge" => PatchType::Wedge,
+            "cyclic" => PatchType::Cyclic,
+            "cyclicAMI" => PatchType::CyclicAMI,
+            "processor" => PatchType::Processor,
+            _ => PatchType::Other(String::from(s))
+        }
+    }
+}
+
+/// Options for parsing list files, e.g., with
+/// `FoamMesh::parse_scalars_with_options`.
+///
+/// The default options skip the `FoamFile` header and recover from
+/// anomalies where possible, like the functions without options do.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail on any anomaly inside a data block, such as empty lines
+    /// (including lines that only hold a comment) or lines without data,
+    /// instead of skipping it.
+    pub strict: bool,
+    /// Number of lines to ignore at the start of a file, see
+    /// `FoamMesh::parse_scalars`.
+    pub skip: Option<usize>,
+}
+
+/// Loads a `FoamMesh` with a choice of components and steps, e.g., to
+/// skip the connectivity if only the points are needed:
+///
+/// ```
+/// # use std::path::PathBuf;
+/// use openfoamparser::FoamMeshBuilder;
+///
+/// let d: PathBuf = [env!("CARGO_MANIFEST_DIR"), "resources/test/cavity/"]
+///     .iter().collect();
+/// let mesh = FoamMeshBuilder::new(&d)
+///     .with_connectivity(false)
+///     .build()
+///     .unwrap();
+/// assert_eq!(mesh.points.len(), 5043);
+/// assert!(mesh.faces.is_empty());
+/// ```
+///
+/// By default, everything `FoamMesh::new` loads is loaded.
+#[derive(Debug, Clone)]
+pub struct FoamMeshBuilder {
+    path: PathBuf,
+    region: String,
+    time: Option<String>,
+    points: bool,
+    connectivity: bool,
+    cell_centers: bool,
+    validate: bool,
+    options: ParseOptions,
+    open: fn(&Path) -> Result<Box<dyn BufRead>, io::Error>,
+}
+
+impl FoamMeshBuilder {
+    /// Start loading the mesh of the case in the directory `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> FoamMeshBuilder {
+        FoamMeshBuilder {
+            path: path.as_ref().to_path_buf(),
+            region: String::new(),
+            time: None,
+            points: true,
+            connectivity: true,
+            cell_centers: false,
+            validate: false,
+            options: ParseOptions::default(),
+            open: open_buffered
+        }
+    }
+
+    /// Load the mesh of the given region, see `FoamMesh::new_region`.
+    pub fn region(mut self, region: &str) -> FoamMeshBuilder {
+        self.region = String::from(region);
+        self
+    }
+
+    /// Load the mesh files from `<time>/polyMesh/` (or
+    /// `<time>/<region>/polyMesh/`), e.g., the points of a moving mesh.
+    ///
+    /// Files missing there are loaded from `constant/` as usual.
+    pub fn time(mut self, time: &str) -> FoamMeshBuilder {
+        self.time = Some(String::from(time));
+        self
+    }
+
+    /// Whether to load the `points` file.
+    pub fn with_points(mut self, load: bool) -> FoamMeshBuilder {
+        self.points = load;
+        self
+    }
+
+    /// Whether to load the `faces`, `owner`, and `neighbour` files and
+    /// construct the cells.
+    ///
+    /// Without the connectivity, `num_cells` and `num_inner_faces` are
+    /// taken from the sizes declared in the header of the `owner` file if
+    /// available. It can still be loaded later on with
+    /// `FoamMesh::ensure_connectivity`.
+    pub fn with_connectivity(mut self, load: bool) -> FoamMeshBuilder {
+        self.connectivity = load;
+        self
+    }
+
+    /// Whether to compute the cell centers, see
+    /// `FoamMesh::compute_cell_centers`.
+    pub fn compute_cell_centers(mut self, compute: bool) -> FoamMeshBuilder {
+        self.cell_centers = compute;
+        self
+    }
+
+    /// Whether to check the mesh, see `FoamMesh::validate`.
+    pub fn validate(mut self, validate: bool) -> FoamMeshBuilder {
+        self.validate = validate;
+        self
+    }
+
+    /// Parse the `points`, `faces`, `owner`, and `neighbour` files with
+    /// the given options.
+    pub fn options(mut self, options: ParseOptions) -> FoamMeshBuilder {
+        self.options = options;
+        self
+    }
+
+    /// Whether to memory-map the mesh files, see `FoamMesh::new_mmap`.
+    #[cfg(feature = "mmap")]
+    pub fn mmap(mut self, mmap: bool) -> FoamMeshBuilder {
+        self.open = if mmap { open_mapped } else { open_buffered };
+        self
+    }
+
+    /// Load the mesh.
+    ///
+    /// Computing the cell centers and validating the mesh require both
+    /// the points and the connectivity.
+    pub fn build(&self) -> Result<FoamMesh, FoamError> {
+        if !(self.points && self.connectivity)
+                && (self.cell_centers || self.validate) {
+            return Err(FoamError::InvalidData(String::from(
+                "Computing cell centers and validating the mesh require its \
+                points and connectivity."
+            )));
         }
+        let pb: PathBuf = poly_mesh_dir(&self.path, &self.region);
+        let mesh_file = |name: &str| -> PathBuf {
+            if let Some(time) = &self.time {
+                let path = self.path.join(time)
+                    .join(&self.region)
+                    .join("polyMesh")
+                    .join(name);
+                if existing_path(&path).exists() {
+                    return path;
+                }
+            }
+            pb.join(name)
+        };
+        let (open, options) = (self.open, self.options);
+        let connectivity = self.connectivity;
+
+        let parse_boundary = || FoamMesh::parse_boundary(
+            mesh_file("boundary"), None);
+        let parse_faces = || {
+            if !connectivity { return Ok(Vec::new()); }
+            let path = mesh_file("faces");
+            FoamMesh::parse_faces_buffered::<_, Index>(
+                open(&path)?, &path, options)
+        };
+        let parse_owners = || {
+            if !connectivity { return Ok(Vec::new()); }
+            let path = mesh_file("owner");
+            FoamMesh::parse_scalars_buffered::<_, Index>(
+                open(&path)?, &path, options)
+        };
+        let parse_neighbors = || {
+            let path = mesh_file("neighbour"); // OpenFoam uses British spelling
+            // Meshes without internal faces may lack the neighbour file:
+            if !connectivity || !existing_path(&path).exists() {
+                return Ok(Vec::new());
+            }
+            FoamMesh::parse_scalars_buffered::<_, i64>(
+                open(&path)?, &path, options)
+        };
+        let parse_points = || {
+            if !self.points { return Ok(Vec::new()); }
+            let path = mesh_file("points");
+            FoamMesh::parse_points_str(
+                &read_reader_to_string(open(&path)?, &path)?,
+                &path,
+                options
+            )
+        };
+
+        // The files are independent of each other:
+        #[cfg(feature = "parallel")]
+        let ((boundary, faces), ((owners, neighbors), points)) = rayon::join(
+            || rayon::join(parse_boundary, parse_faces),
+            || rayon::join(
+                || rayon::join(parse_owners, parse_neighbors),
+                parse_points
+            )
+        );
+        #[cfg(not(feature = "parallel"))]
+        let ((boundary, faces), ((owners, neighbors), points)) = (
+            (parse_boundary(), parse_faces()),
+            ((parse_owners(), parse_neighbors()), parse_points())
+        );
+        let boundary: HashMap<String, Boundary> = boundary?;
+        let faces: Vec<Vec<Index>> = faces?;
+        let owners: Vec<Index> = owners?;
+        let mut neighbors: Vec<i64> = neighbors?;
+        let points: Vec<Point3<f64>> = points?;
+
+        // The owner file usually declares the mesh sizes, which catches
+        // truncated or mismatched files:
+        let declared_sizes: HashMap<String, usize> =
+            parse_header(mesh_file("owner"))
+            .ok()
+            .and_then(|header| header.note)
+            .map_or_else(HashMap::new, |note| parse_mesh_sizes(&note));
+
+        let num_faces = owners.len();
+        let (num_inner_faces, num_cells) = if connectivity {
+            // Cells that own no face still appear as neighbors:
+            (neighbors.len(), (std::cmp::max(
+                owners.iter().max().map_or(-1, |&o| idx(o) as i64),
+                *neighbors.iter().max().unwrap_or(&-1)
+            ) + 1) as usize)
+        } else {
+            (
+                declared_sizes.get("nInternalFaces").copied()
+                    .or_else(|| boundary.values().map(|b| b.start_face).min())
+                    .unwrap_or(0),
+                declared_sizes.get("nCells").copied().unwrap_or(0)
+            )
+        };
+        // All indices stored in the mesh must fit into `Index`:
+        to_index(points.len())?;
+        to_index(num_faces)?;
+        to_index(num_cells)?;
 
-        // _construct_cells:
-        let cell_num: usize = std::cmp::max(
-            num_cells as i64,
-            *neighbors.iter().max().unwrap()
-        ) as usize + 1;
-        let mut cell_faces: Vec<Vec<usize>> = vec![Vec::new(); cell_num];
-        let mut cell_neighbors: Vec<Vec<i64>> = vec![Vec::new(); cell_num];
-        for (i, &owner) in owners.iter().enumerate() {
-            cell_faces[owner].push(i);
+        let mut found_sizes: Vec<(&str, usize)> = Vec::new();
+        if self.points {
+            found_sizes.push(("nPoints", points.len()));
         }
-        for (i, &neighbor) in neighbors.iter().enumerate() {
-            if neighbor >= 0 {
-                cell_faces[neighbor as usize].push(i);
-                cell_neighbors[neighbor as usize].push(owners[i] as i64);
+        if connectivity {
+            found_sizes.extend_from_slice(&[
+                ("nCells", num_cells),
+                ("nFaces", num_faces),
+                ("nInternalFaces", num_inner_faces)
+            ]);
+        }
+        for &(key, found) in &found_sizes {
+            match declared_sizes.get(key) {
+                Some(&expected) if expected != found => {
+                    return Err(FoamError::InvalidData(format!(
+                        "The mesh in \"{}\" declares {}:{}, but has {}.",
+                        pb.to_string_lossy(),
+                        key,
+                        expected,
+                        found
+                    )));
+                },
+                _ => {}
             }
-            cell_neighbors[owners[i]].push(neighbor);
         }
 
-        Ok(FoamMesh {
-            path: PathBuf::new().join(&path),
+        let (cell_faces, cell_neighbors) = if connectivity {
+            check_boundary_faces(&boundary, num_inner_faces, num_faces)?;
+
+            // _set_boundary_faces:
+            neighbors.extend(vec![-10; num_faces - num_inner_faces]);
+            for b in boundary.values() {
+                for neighbor in &mut neighbors[
+                        b.start_face .. b.start_face + b.num_faces] {
+                    *neighbor = b.boundary_id;
+                }
+            }
+            construct_cells(&owners, &neighbors, num_cells)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let mut mesh = FoamMesh {
+            path: self.path.clone(),
+            region: self.region.clone(),
             boundary,
-            points: FoamMesh::parse_points(&pb.join("points"), 10)?,
+            points,
             faces,
             cell_faces,
             owners,
@@ -156,8 +601,211 @@ impl FoamMesh {
             cell_neighbors,
             num_inner_faces,
             num_cells,
-            cell_centers: None
-        })
+            cell_centers: None,
+            #[cfg(feature = "kdtree")]
+            cell_index: None,
+            cell_zones: HashMap::new(),
+            face_zones: HashMap::new(),
+            point_zones: HashMap::new()
+        };
+        mesh.cell_zones = mesh.parse_cell_zones()?;
+        mesh.face_zones = mesh.parse_face_zones()?;
+        mesh.point_zones = mesh.parse_point_zones()?;
+        if self.validate {
+            mesh.validate()?;
+        }
+        if self.cell_centers {
+            mesh.compute_cell_centers();
+        }
+        Ok(mesh)
+    }
+}
+
+impl FoamMesh {
+    /// Load the mesh from `constant/polyMesh/` in the given case directory.
+    ///
+    /// Compressed mesh files (e.g., `points.gz`) are used in place of
+    /// missing uncompressed ones.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<FoamMesh, FoamError> {
+        FoamMesh::new_region(path, "")
+    }
+
+    /// Load the mesh like `new`, parsing the `points`, `faces`, `owner`,
+    /// and `neighbour` files with the given options.
+    pub fn new_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ParseOptions
+    ) -> Result<FoamMesh, FoamError> {
+        FoamMeshBuilder::new(path).options(options).build()
+    }
+
+    /// Load the mesh like `new`, and check its consistency using
+    /// `validate`.
+    pub fn new_validated<P: AsRef<Path>>(
+        path: P
+    ) -> Result<FoamMesh, FoamError> {
+        let mesh = FoamMesh::new(path)?;
+        mesh.validate()?;
+        Ok(mesh)
+    }
+
+    /// Load the faces and construct the cells if the mesh was loaded
+    /// without its connectivity (see `FoamMeshBuilder::with_connectivity`),
+    /// e.g., before accessing the neighbors of a cell.
+    ///
+    /// Does nothing if the mesh already has faces. Otherwise, the `faces`,
+    /// `owner`, and `neighbour` files are loaded from the mesh directory in
+    /// `constant/`, and all connectivity fields (`faces`, `owners`,
+    /// `neighbors`, `cell_faces`, and `cell_neighbors`) are replaced.
+    pub fn ensure_connectivity(&mut self) -> Result<(), FoamError> {
+        if !self.faces.is_empty() { return Ok(()); }
+        let loaded = FoamMeshBuilder::new(&self.path)
+            .region(&self.region)
+            .with_points(false)
+            .build()?;
+        if !self.points.is_empty() {
+            // The connectivity must match the points loaded before:
+            if let Some(point) = loaded.faces.iter()
+                    .flatten()
+                    .find(|&&p| idx(p) >= self.points.len()) {
+                return Err(FoamError::InvalidData(format!(
+                    "The faces reference point {}, but there are only {} \
+                    points.",
+                    point, self.points.len()
+                )));
+            }
+        }
+        self.faces = loaded.faces;
+        self.owners = loaded.owners;
+        self.neighbors = loaded.neighbors;
+        self.cell_faces = loaded.cell_faces;
+        self.cell_neighbors = loaded.cell_neighbors;
+        self.num_inner_faces = loaded.num_inner_faces;
+        self.num_cells = loaded.num_cells;
+        Ok(())
+    }
+
+    /// Load the mesh of the given region from `constant/<region>/polyMesh/`
+    /// in the given case directory, e.g., the `fluid` or `solid` region
+    /// of a multi-region case.
+    ///
+    /// An empty `region` refers to the default mesh in
+    /// `constant/polyMesh/`.
+    pub fn new_region<P: AsRef<Path>>(
+        path: P,
+        region: &str
+    ) -> Result<FoamMesh, FoamError> {
+        FoamMeshBuilder::new(path).region(region).build()
+    }
+
+    /// Load the mesh like `new`, but memory-map the `points`, `faces`,
+    /// `owner`, and `neighbour` files instead of reading them, which lets
+    /// the operating system page in their contents as they are parsed.
+    ///
+    /// The mesh files must not be modified while the mesh is loaded.
+    #[cfg(feature = "mmap")]
+    pub fn new_mmap<P: AsRef<Path>>(path: P) -> Result<FoamMesh, FoamError> {
+        FoamMeshBuilder::new(path).mmap(true).build()
+    }
+
+    /// Load the meshes of a decomposed case, i.e., one mesh per
+    /// `processorN/` directory in `case_dir`, ordered by processor number.
+    pub fn new_decomposed<P: AsRef<Path>>(
+        case_dir: P
+    ) -> Result<Vec<FoamMesh>, FoamError> {
+        processor_dirs(case_dir)?.iter().map(FoamMesh::new).collect()
+    }
+
+    /// Parse the cell zones defined in `constant/polyMesh/cellZones`,
+    /// mapping each zone name to its cell indices.
+    ///
+    /// Returns an empty map if the file does not exist.
+    pub fn parse_cell_zones(
+        &self
+    ) -> Result<HashMap<String, Vec<usize>>, FoamError> {
+        parse_zones(
+            poly_mesh_dir(&self.path, &self.region).join("cellZones"))?
+            .into_iter()
+            .map(|(name, entries)| {
+                let labels = parse_zone_labels(&name, &entries, "cellLabels")?;
+                Ok((name, labels))
+            })
+            .collect()
+    }
+
+    /// Parse the face zones defined in `constant/polyMesh/faceZones`.
+    ///
+    /// Returns an empty map if the file does not exist.
+    pub fn parse_face_zones(
+        &self
+    ) -> Result<HashMap<String, FaceZone>, FoamError> {
+        parse_zones(
+            poly_mesh_dir(&self.path, &self.region).join("faceZones"))?
+            .into_iter()
+            .map(|(name, entries)| {
+                let faces = parse_zone_labels(&name, &entries, "faceLabels")?;
+                let flip_map = parse_zone_labels(&name, &entries, "flipMap")?
+                    .into_iter()
+                    .map(|flip| flip != 0)
+                    .collect::<Vec<bool>>();
+                if flip_map.len() != faces.len() {
+                    return Err(FoamError::InvalidData(format!(
+                        "Face zone \"{}\" has {} faces, but a flip map \
+                        of length {}.",
+                        name,
+                        faces.len(),
+                        flip_map.len()
+                    )));
+                }
+                Ok((name, FaceZone { faces, flip_map }))
+            })
+            .collect()
+    }
+
+    /// Parse the point zones defined in `constant/polyMesh/pointZones`,
+    /// mapping each zone name to its point indices.
+    ///
+    /// Returns an empty map if the file does not exist.
+    pub fn parse_point_zones(
+        &self
+    ) -> Result<HashMap<String, Vec<usize>>, FoamError> {
+        parse_zones(
+            poly_mesh_dir(&self.path, &self.region).join("pointZones"))?
+            .into_iter()
+            .map(|(name, entries)| {
+                let labels = parse_zone_labels(&name, &entries, "pointLabels")?;
+                Ok((name, labels))
+            })
+            .collect()
+    }
+
+    /// Load the sets in `constant/polyMesh/sets/`, e.g., written by
+    /// `topoSet`, mapping each set name to its kind and indices.
+    ///
+    /// Files of other classes than `cellSet`, `faceSet`, and `pointSet`
+    /// are skipped.
+    /// Returns an empty map if the directory does not exist.
+    pub fn load_sets(
+        &self
+    ) -> Result<HashMap<String, (SetKind, Vec<usize>)>, FoamError> {
+        let dir = poly_mesh_dir(&self.path, &self.region).join("sets");
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(HashMap::new());
+            },
+            Err(e) => return Err(e.into())
+        };
+        let mut sets: HashMap<String, (SetKind, Vec<usize>)> = HashMap::new();
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() { continue; }
+            let header = parse_header(entry.path())?;
+            if let Some(kind) = SetKind::from_class(&header.class) {
+                sets.insert(header.object, (kind, parse_set(entry.path())?));
+            }
+        }
+        Ok(sets)
     }
 
     /// Read cell center coordinates from the given file
@@ -167,633 +815,7581 @@ impl FoamMesh {
     /// `postProcess -func 'writeCellCentres' -time 0`.
     pub fn read_cell_centers<P: AsRef<Path>>(
         &mut self, filename: P
-    ) -> Result<(), io::Error> {
+    ) -> Result<(), FoamError> {
         self.cell_centers = Some(parse_internal_field(
             filename,
-            |s| parse_point3(s)
+            parse_point3
         )?);
         Ok(())
     }
 
-    pub fn num_inner_faces(&self) -> usize {
-        return self.num_inner_faces;
-    }
-
-    pub fn num_cells(&self) -> usize {
-        return self.num_cells;
+    /// Check the consistency of the mesh, i.e., that
+    /// - each face has an owner cell,
+    /// - all owner and neighbor cells exist,
+    /// - all faces only consist of existing points,
+    /// - and all boundary patches consist of existing boundary faces,
+    ///   i.e., they follow the internal faces.
+    ///
+    /// Corrupt mesh files may otherwise lead to panics later on, e.g.,
+    /// when computing cell centers.
+    pub fn validate(&self) -> Result<(), FoamError> {
+        let invalid = |msg: String| Err(FoamError::InvalidData(msg));
+        if self.owners.len() != self.faces.len() {
+            return invalid(format!(
+                "The mesh has {} faces, but {} owners.",
+                self.faces.len(),
+                self.owners.len()
+            ));
+        }
+        let num_cells = self.cell_faces.len();
+        if let Some((face_id, owner)) = self.owners.iter()
+                .enumerate()
+                .find(|(_, &owner)| idx(owner) >= num_cells) {
+            return invalid(format!(
+                "Face {} is owned by cell {}, but there are only {} cells.",
+                face_id, owner, num_cells
+            ));
+        }
+        if let Some((face_id, neighbor)) = self.neighbors.iter()
+                .take(self.num_inner_faces)
+                .enumerate()
+                .find(|(_, &n)| n < 0 || n as usize >= num_cells) {
+            return invalid(format!(
+                "Internal face {} has the neighbor {}, but there are only \
+                {} cells.",
+                face_id, neighbor, num_cells
+            ));
+        }
+        for (face_id, face) in self.faces.iter().enumerate() {
+            if let Some(point) = face.iter()
+                    .find(|&&p| idx(p) >= self.points.len()) {
+                return invalid(format!(
+                    "Face {} references point {}, but there are only {} \
+                    points.",
+                    face_id, point, self.points.len()
+                ));
+            }
+        }
+        check_boundary_faces(
+            &self.boundary, self.num_inner_faces, self.faces.len())
     }
 
-    /// Return the indices of neighbor cells of the cell with index `cell_id`.
-    pub fn cell_neighbor_cells(&self, cell_id: usize) -> Option<&Vec<i64>> {
-        self.cell_neighbors.get(cell_id)
+    /// Find pairs of distinct points at a distance of at most `tol`, e.g.,
+    /// coincident points of stitched meshes.
+    ///
+    /// Each pair `(i, j)` has `i < j`, and the pairs are sorted.
+    /// The points are bucketed by a grid with a spacing of `tol`, such that
+    /// only points in neighboring buckets need to be compared.
+    pub fn find_duplicate_points(&self, tol: f64) -> Vec<(usize, usize)> {
+        let spacing = if tol > 0.0 { tol } else { 1.0 };
+        let bucket = |p: &Point3<f64>| -> [i64; 3] {
+            [
+                (p.x / spacing).floor() as i64,
+                (p.y / spacing).floor() as i64,
+                (p.z / spacing).floor() as i64
+            ]
+        };
+        let mut buckets: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for (j, p) in self.points.iter().enumerate() {
+            let [x, y, z] = bucket(p);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let key = [
+                            x.saturating_add(dx),
+                            y.saturating_add(dy),
+                            z.saturating_add(dz)
+                        ];
+                        pairs.extend(buckets.get(&key)
+                            .into_iter()
+                            .flatten()
+                            .filter(|&&i| (self.points[i] - p).norm() <= tol)
+                            .map(|&i| (i, j)));
+                    }
+                }
+            }
+            buckets.entry([x, y, z]).or_default().push(j);
+        }
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
     }
 
-    /// Check if a cell is on a boundary.
+    /// Merge points at a distance of at most `tol` (see
+    /// `find_duplicate_points`) into the one with the lowest index, and
+    /// remove the others from `points`.
     ///
-    /// Run-time complexity is in O(n), where n is the maximum number of
-    /// neighbors of a cell.
-    pub fn is_cell_on_boundary(
-        &self,
-        cell_id: usize,
-        bd_name: Option<String>
-    ) -> bool {
-        if cell_id >= self.num_cells { return false; }
-        let mut bid: i64 = 0;
-        if let Some(bd_name) = &bd_name {
-            if let Some(bd) = self.boundary.get(bd_name) {
-                bid = bd.boundary_id;
+    /// The point indices of `faces` and `point_zones` are updated
+    /// accordingly. Faces are not checked for becoming degenerate.
+    /// Returns the number of removed points.
+    pub fn merge_points(&mut self, tol: f64) -> usize {
+        let mut canonical: Vec<usize> = (0..self.points.len()).collect();
+        let mut pairs = self.find_duplicate_points(tol);
+        // Process the pairs by their second point, such that chains of
+        // close points are merged transitively:
+        pairs.sort_unstable_by_key(|&(i, j)| (j, i));
+        for (i, j) in pairs {
+            canonical[j] = canonical[j].min(canonical[i]);
+        }
+        let mut new_ids: Vec<usize> = vec![0; self.points.len()];
+        let mut num_kept: usize = 0;
+        for i in 0..canonical.len() {
+            if canonical[i] == i {
+                new_ids[i] = num_kept;
+                num_kept += 1;
             } else {
-                return false;
+                new_ids[i] = new_ids[canonical[i]];
             }
         }
-        for &neighbor in self.cell_neighbors[cell_id].iter() {
-            if bd_name == None && neighbor < 0 {
-                return true;
-            } else if bd_name != None && neighbor == bid {
-                return true;
+        let num_removed = self.points.len() - num_kept;
+        if num_removed == 0 {
+            return 0;
+        }
+        let mut i: usize = 0;
+        self.points.retain(|_| {
+            i += 1;
+            canonical[i - 1] == i - 1
+        });
+        for face in &mut self.faces {
+            for p in face.iter_mut() {
+                // There are fewer points than before, so they fit `Index`:
+                *p = new_ids[idx(*p)] as Index;
             }
         }
-        false
-    }
-
-    /// Check if a face is a boundary face (in O(1)).
-    pub fn is_face_on_boundary(
-        &self,
-        face_id: usize,
-        bd_name: Option<String>
-    ) -> bool {
-        if face_id >= self.faces.len() { return false; }
-        if let Some(bd_name) = &bd_name {
-            if let Some(bd) = self.boundary.get(bd_name) {
-                return self.neighbors[face_id] == bd.boundary_id;
-            } else {
-                false
+        for points in self.point_zones.values_mut() {
+            for p in points.iter_mut() {
+                *p = new_ids[*p];
             }
-        } else {
-            return self.neighbors[face_id] < 0;
+            points.sort_unstable();
+            points.dedup();
         }
+        num_removed
     }
 
-    /// Get cell IDs of cells on a given boundary.
-    /// Returns an empty vector if the named boundary does not exist.
-    pub fn boundary_cells(&self, bd_name: &str) -> Vec<usize> {
-        if let Some(bd) = self.boundary.get(bd_name) {
-            (bd.start_face .. bd.start_face + bd.num_faces)
-                .map(|face_id| self.owners[face_id])
-                .collect()
-        } else { vec![] }
+    pub fn num_inner_faces(&self) -> usize {
+        self.num_inner_faces
     }
 
-    /// Parse scalar values from a given ASCII file.
+    pub fn num_cells(&self) -> usize {
+        self.num_cells
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn num_boundary_faces(&self) -> usize {
+        self.faces.len() - self.num_inner_faces
+    }
+
+    /// Copy `faces` into the compact `FlatFaces` layout, e.g., to pass
+    /// them on as CSR arrays.
     ///
-    /// Expects a file in the following format:
-    /// ```plaintext
-    /// // …
+    /// The mesh itself keeps its faces in the nested layout; to avoid it
+    /// for large face files, use `parse_faces_flat` instead.
+    /// Fails if a point index does not fit into a `u32`.
+    pub fn flat_faces(&self) -> Result<FlatFaces, FoamError> {
+        flatten_faces(&self.faces)
+    }
+
+    /// Get the minimum and maximum coordinates of the mesh points, or
+    /// `None` if the mesh has no points.
+    pub fn bounding_box(&self) -> Option<(Point3<f64>, Point3<f64>)> {
+        let first = self.points.first()?;
+        Some(self.points.iter().fold((*first, *first), |(min, max), p| {
+            (min.inf(p), max.sup(p))
+        }))
+    }
+
+    /// Describe the mesh for a quick sanity check, i.e., its sizes (see
+    /// the `Display` implementation), bounding box, and boundary patches
+    /// with their types, e.g.:
     ///
-    /// 11360
-    /// (
-    /// 42
-    /// 0
-    /// 3
-    /// // …
-    /// )
+    /// ```plaintext
+    /// 3200 cells, 11360 faces (7840 internal), 5043 points
+    /// bounding box: (0, 0, 0) to (0.1, 0.1, 0.01)
+    /// 3 patches:
+    ///     movingWall (wall): 80 faces
+    ///     fixedWalls (wall): 240 faces
+    ///     frontAndBack (empty): 3200 faces
     /// ```
-    pub fn parse_scalars<P: AsRef<Path>, T: std::str::FromStr>(
-        filename: P,
-        skip: usize
-    ) -> Result<Vec<T>, io::Error> {
-        let mut data: Vec<T> = Vec::new();
-        let mut num_expected: usize = 0;
-        for line in read_to_string(&filename)?
-                .split('\n').skip(skip) {
-            if num_expected > 0 {
-                if let Ok(val) = line.parse::<T>() {
-                    data.push(val);
-                }
-            } else if let Ok(num_values) = line.parse::<usize>() {
-                num_expected = num_values;
-            }
+    pub fn summary(&self) -> String {
+        use std::fmt::Write as _;
+        let mut s = String::with_capacity(128 + 64 * self.boundary.len());
+        // Writing to a `String` cannot fail:
+        writeln!(s, "{}", self).unwrap();
+        if let Some((min, max)) = self.bounding_box() {
+            writeln!(s, "bounding box: ({}, {}, {}) to ({}, {}, {})",
+                min.x, min.y, min.z, max.x, max.y, max.z).unwrap();
         }
-        if data.len() != num_expected {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "{} values expected, but parsed {}.",
-                    num_expected,
-                    data.len()
-                )
-            ));
+        writeln!(s, "{} patches:", self.boundary.len()).unwrap();
+        for (name, bd) in self.boundaries_ordered() {
+            writeln!(s, "    {} ({}): {} faces",
+                name, bd.boundary_type, bd.num_faces).unwrap();
         }
-        Ok(data)
+        s
     }
 
-    /// Parse faces from a given ASCII file.
-    /// Each face is a list of point indices.
-    ///
-    /// Expects a file in the following format:
-    /// ```plaintext
-    /// // …
+    /// Guess whether a field with `len` values is defined on the cells
+    /// (e.g., a `volScalarField`) or faces (e.g., a `surfaceScalarField`
+    /// such as `phi`) of the mesh.
     ///
-    /// 11360
-    /// (
-    /// 4(1 42 1723 1682)
-    /// 3(2 3 4)
-    /// // …
-    /// )
-    /// ```
-    pub fn parse_faces<P: AsRef<Path>>(
-        filename: P,
-        skip: usize
-    ) -> Result<Vec<Vec<usize>>, io::Error> {
-        lazy_static! {
-            static ref RE_NUM: Regex = Regex::new(
-                r"\d+"
-            ).unwrap();
+    /// The internal field of a surface field only holds values of the
+    /// internal faces, but fields of all faces are recognized as well.
+    /// Cells take precedence if the lengths are ambiguous; use
+    /// `FieldLocation::from_class` if the field's class is known.
+    pub fn classify_field_length(&self, len: usize) -> FieldLocation {
+        if len == self.num_cells {
+            FieldLocation::Cell
+        } else if len == self.num_inner_faces || len == self.faces.len() {
+            FieldLocation::Face
+        } else {
+            FieldLocation::Unknown
         }
+    }
 
-        let mut data: Vec<Vec<usize>> = Vec::new();
-        let mut num_faces_expected: usize = 0;
-        for (i, line) in read_to_string(&filename)?
-                .split('\n')
-                .skip(skip)
-                .enumerate() {
-            if num_faces_expected > 0 {
-                // We already encountered the initial line stating
-                // the number of expected faces.
-                // Now read the actual data.
-                let mut vals: Vec<usize> = RE_NUM.captures_iter(&line)
-                    .map(|cap| cap[0].parse::<usize>().unwrap())
-                    .collect();
-                if vals.len() == 0 { continue; }
-                if vals.len() != vals[0] + 1 {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "Malformed faces file, l. {} (\"{}\"): \
-                            Mismatch between number of vertices announced \
-                            and found.",
-                            skip+i,
-                            line
-                        )
-                    ));
-                }
-                vals.remove(0);
-                data.push(vals);
-            } else if let Ok(num_faces) = line.parse::<usize>() {
-                num_faces_expected = num_faces;
-            }
-        }
-        if data.len() != num_faces_expected {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "{} faces expected, but parsed {}.",
-                    num_faces_expected,
-                    data.len()
-                )
-            ));
-        }
-        Ok(data)
+    /// Get the owner cell of the face with index `face_id`.
+    pub fn face_owner(&self, face_id: usize) -> Option<usize> {
+        self.owners.get(face_id).map(|&owner| idx(owner))
     }
 
-    /// Parse mesh point data from a given ASCII file.
-    ///
-    /// Expects a file in the following format:
-    /// ```plaintext
-    /// // …
+    /// Get the neighbor cell of the face with index `face_id`.
     ///
-    /// 5043
-    /// (
-    /// (42 0 1)
-    /// (3 2.001 13.37)
-    /// // …
-    /// )
-    /// ```
-    pub fn parse_points<P: AsRef<Path>>(
-        filename: P,
-        skip: usize
-    ) -> Result<Vec<Point3<f64>>, io::Error> {
-        let mut num_points_expected: usize = 0;
-        let mut data: Vec<Point3<f64>> = Vec::new();
-        for (i, line) in read_to_string(&filename)?
-                .split('\n')
-                .skip(skip)
-                .enumerate() {
-            if num_points_expected > 0 {
-                // We already encountered the initial line stating
-                // the number of expected points.
-                // Now read the actual data.
-                if !line.starts_with('(') || !line.ends_with(')') {
-                    continue;
+    /// For boundary faces, this is the (negative) `boundary_id` of the
+    /// respective boundary.
+    pub fn face_neighbor(&self, face_id: usize) -> Option<i64> {
+        self.neighbors.get(face_id).cloned()
+    }
+
+    /// Check if the face with index `face_id` is an internal face, i.e.,
+    /// it has a neighbor cell.
+    pub fn face_is_internal(&self, face_id: usize) -> bool {
+        face_id < self.num_inner_faces
+    }
+
+    /// Find the boundary patch with the given (negative) `boundary_id`, as
+    /// stored in `neighbors` for boundary faces.
+    pub fn boundary_by_id(&self, id: i64) -> Option<(&String, &Boundary)> {
+        self.boundary.iter().find(|(_, b)| b.boundary_id == id)
+    }
+
+    /// List the boundary patches in the order of their faces, i.e., sorted
+    /// by `start_face`.
+    pub fn boundaries_ordered(&self) -> Vec<(&String, &Boundary)> {
+        let mut patches: Vec<(&String, &Boundary)> =
+            self.boundary.iter().collect();
+        patches.sort_by_key(|(_, b)| b.start_face);
+        patches
+    }
+
+    /// Get the name of the boundary patch that the face with index
+    /// `face_id` belongs to, or `None` for internal faces.
+    pub fn boundary_name_of_face(&self, face_id: usize) -> Option<&String> {
+        self.boundary.iter()
+            .find(|(_, b)| {
+                (b.start_face..b.start_face + b.num_faces).contains(&face_id)
+            })
+            .map(|(name, _)| name)
+    }
+
+    /// List the time directories of the mesh's case.
+    /// See the free function `time_directories`.
+    pub fn time_directories(&self) -> Result<Vec<f64>, FoamError> {
+        time_directories(&self.path)
+    }
+
+    /// Get the latest time of the mesh's case.
+    /// See the free function `latest_time`.
+    pub fn latest_time(&self) -> Result<Option<f64>, FoamError> {
+        latest_time(&self.path)
+    }
+
+    /// Return the indices of neighbor cells of the cell with index `cell_id`.
+    ///
+    /// Returns `None` if the mesh was loaded without its connectivity, see
+    /// `load_cell_neighbor_cells`.
+    pub fn cell_neighbor_cells(&self, cell_id: usize) -> Option<&Vec<i64>> {
+        self.cell_neighbors.get(cell_id)
+    }
+
+    /// Like `cell_neighbor_cells`, but first loads the connectivity with
+    /// `ensure_connectivity` if the mesh was loaded without it.
+    pub fn load_cell_neighbor_cells(
+        &mut self,
+        cell_id: usize
+    ) -> Result<Option<&Vec<i64>>, FoamError> {
+        self.ensure_connectivity()?;
+        Ok(self.cell_neighbor_cells(cell_id))
+    }
+
+    /// Compute a new cell ordering with the reverse Cuthill-McKee
+    /// algorithm, which reduces the bandwidth of the cell adjacency
+    /// matrix, like OpenFOAM's `renumberMesh`.
+    ///
+    /// Returns a permutation listing the old index of each cell in the
+    /// new ordering, see `apply_permutation`.
+    pub fn reverse_cuthill_mckee(&self) -> Vec<usize> {
+        let (row_offsets, col_indices) = self.cell_adjacency_csr();
+        let num_cells = row_offsets.len() - 1;
+        let degree = |cell_id: usize| {
+            row_offsets[cell_id + 1] - row_offsets[cell_id]
+        };
+        let mut by_degree: Vec<usize> = (0..num_cells).collect();
+        by_degree.sort_by_key(|&cell_id| degree(cell_id));
+        let mut visited = vec![false; num_cells];
+        let mut order: Vec<usize> = Vec::with_capacity(num_cells);
+        // Each connected region starts at its cell of the lowest degree:
+        for start in by_degree {
+            if visited[start] { continue; }
+            visited[start] = true;
+            let mut next = order.len();
+            order.push(start);
+            while next < order.len() {
+                let cell_id = order[next];
+                next += 1;
+                let mut neighbors: Vec<usize> = col_indices[
+                        row_offsets[cell_id]..row_offsets[cell_id + 1]]
+                    .iter()
+                    .copied()
+                    .filter(|&neighbor| !visited[neighbor])
+                    .collect();
+                neighbors.sort_by_key(|&neighbor| degree(neighbor));
+                for neighbor in neighbors {
+                    // Cells may share several faces:
+                    if visited[neighbor] { continue; }
+                    visited[neighbor] = true;
+                    order.push(neighbor);
                 }
-                if let Some(v) = parse_point3(line) {
-                    data.push(v);
-                } else {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!(
-                            "Malformed points file, l. {} (\"{}\"): \
-                            Could not parse three floats.",
-                            skip+i,
-                            line
-                        )
-                    ));
+            }
+        }
+        order.reverse();
+        order
+    }
+
+    /// Renumber the cells such that cell `perm[i]` becomes cell `i`, e.g.,
+    /// with the permutation from `reverse_cuthill_mckee`.
+    ///
+    /// Updates the owners and neighbors of the faces, the cells' faces and
+    /// neighbors, `cell_centers`, and `cell_zones`.
+    /// Internal faces whose new owner would be greater than their new
+    /// neighbor are flipped, following the OpenFOAM convention; faces are
+    /// not reordered.
+    /// Cell fields can be renumbered with `permute_field`.
+    pub fn apply_permutation(
+        &mut self,
+        perm: &[usize]
+    ) -> Result<(), FoamError> {
+        let num_cells = self.cell_faces.len();
+        let mut new_ids: Vec<usize> = vec![usize::MAX; num_cells];
+        for (new_id, &old_id) in perm.iter().enumerate() {
+            match new_ids.get_mut(old_id) {
+                Some(id) if *id == usize::MAX => *id = new_id,
+                _ => return Err(FoamError::InvalidData(format!(
+                    "Invalid cell permutation: cell {} is unknown or \
+                    listed twice.",
+                    old_id
+                )))
+            }
+        }
+        if perm.len() != num_cells {
+            return Err(FoamError::CountMismatch {
+                expected: num_cells,
+                found: perm.len()
+            });
+        }
+        for owner in &mut self.owners {
+            *owner = new_ids[idx(*owner)] as Index;
+        }
+        let mut flipped: HashSet<usize> = HashSet::new();
+        for face_id in 0..self.num_inner_faces {
+            let neighbor = new_ids[self.neighbors[face_id] as usize];
+            let owner = idx(self.owners[face_id]);
+            if owner > neighbor {
+                self.owners[face_id] = neighbor as Index;
+                self.neighbors[face_id] = owner as i64;
+                self.faces[face_id].reverse();
+                flipped.insert(face_id);
+            } else {
+                self.neighbors[face_id] = neighbor as i64;
+            }
+        }
+        let (cell_faces, cell_neighbors) =
+            construct_cells(&self.owners, &self.neighbors, num_cells);
+        self.cell_faces = cell_faces;
+        self.cell_neighbors = cell_neighbors;
+        if let Some(centers) = &self.cell_centers {
+            self.cell_centers = Some(permute_field(centers, perm));
+        }
+        for cells in self.cell_zones.values_mut() {
+            for cell_id in cells.iter_mut() {
+                *cell_id = new_ids[*cell_id];
+            }
+        }
+        for zone in self.face_zones.values_mut() {
+            for (face_id, flip) in zone.faces.iter().zip(&mut zone.flip_map) {
+                if flipped.contains(face_id) {
+                    *flip = !*flip;
                 }
-            } else if let Ok(num_points) = line.parse::<usize>() {
-                num_points_expected = num_points;
             }
         }
-        if data.len() != num_points_expected {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "{} points expected, but parsed {}.",
-                    num_points_expected,
-                    data.len()
-                )
-            ));
+        #[cfg(feature = "kdtree")]
+        {
+            self.cell_index = None;
         }
-        Ok(data)
+        Ok(())
     }
 
-    /// Parse an OpenFOAM boundary definition file.
+    /// Label each cell with the index of its connected region, i.e., the
+    /// regions of cells connected via internal faces.
     ///
-    /// Expects a file in the following format:
-    /// ```plaintext
-    /// // …
+    /// Regions are numbered in the order of their lowest cell index, so
+    /// all cells of a connected mesh are labeled 0.
+    pub fn connected_components(&self) -> Vec<usize> {
+        let (row_offsets, col_indices) = self.cell_adjacency_csr();
+        let num_cells = row_offsets.len() - 1;
+        let mut components: Vec<usize> = vec![usize::MAX; num_cells];
+        let mut num_components: usize = 0;
+        let mut queue: Vec<usize> = Vec::new();
+        for start in 0..num_cells {
+            if components[start] != usize::MAX { continue; }
+            components[start] = num_components;
+            queue.push(start);
+            while let Some(cell_id) = queue.pop() {
+                for &neighbor in &col_indices[
+                        row_offsets[cell_id]..row_offsets[cell_id + 1]] {
+                    if components[neighbor] == usize::MAX {
+                        components[neighbor] = num_components;
+                        queue.push(neighbor);
+                    }
+                }
+            }
+            num_components += 1;
+        }
+        components
+    }
+
+    /// Get the number of connected regions, see `connected_components`.
+    pub fn num_components(&self) -> usize {
+        self.connected_components()
+            .iter()
+            .max()
+            .map_or(0, |&component| component + 1)
+    }
+
+    /// Get the cell adjacency graph in compressed sparse row (CSR) format
+    /// as `(row_offsets, col_indices)`.
     ///
-    /// 3
-    /// (
-    ///     inlet
-    ///     {
-    ///         type            patch;
-    ///         nFaces          605;
-    ///         startFace       971201;
-    ///     }
-    ///     outlet
-    ///     {
-    ///         type            patch;
-    ///         nFaces          605;
-    ///         startFace       971806;
-    ///     }
-    ///     walls
-    ///     {
-    ///         type            patch;
-    ///         nFaces          23848;
-    ///         startFace       972411;
-    ///     }
-    /// )
-    /// ```
-    pub fn parse_boundary<P: AsRef<Path>>(
-        filename: P,
-        skip: usize
-    ) -> Result<HashMap<String, Boundary>, std::io::Error> {
-        // TODO: This, like the reference implementation, relies an
-        //  awful lot on an expected number of newlines between elements…
-        fn get_val(line: &str) -> Result<&str, std::io::Error> {
-            // example: "        nFaces          605;" -> "605"
-            if let Some(val_str) = line.split(' ')
-                    .filter(|s| !s.is_empty()).nth(1) {
-                if let Some(val_str) = val_str.strip_suffix(";") {
-                    return Ok(val_str)
+    /// The neighbors of cell `i` are
+    /// `col_indices[row_offsets[i]..row_offsets[i + 1]]`.
+    /// Unlike `cell_neighbors`, this only contains actual cells, not
+    /// boundaries.
+    pub fn cell_adjacency_csr(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut row_offsets: Vec<usize> =
+            Vec::with_capacity(self.cell_neighbors.len() + 1);
+        let mut col_indices: Vec<usize> =
+            Vec::with_capacity(2 * self.num_inner_faces);
+        row_offsets.push(0);
+        for neighbors in &self.cell_neighbors {
+            col_indices.extend(neighbors.iter()
+                .filter(|&&neighbor| neighbor >= 0)
+                .map(|&neighbor| neighbor as usize));
+            row_offsets.push(col_indices.len());
+        }
+        (row_offsets, col_indices)
+    }
+
+    /// Get the (deduplicated and sorted) indices of the points of all
+    /// faces of the cell with index `cell_id`.
+    ///
+    /// Returns an empty vector for unknown cells.
+    pub fn cell_points(&self, cell_id: usize) -> Vec<usize> {
+        let mut points: Vec<usize> = match self.cell_faces.get(cell_id) {
+            Some(faces) => faces.iter()
+                .flat_map(|&face_id| self.faces[idx(face_id)].iter()
+                    .map(|&point| idx(point)))
+                .collect(),
+            None => return Vec::new()
+        };
+        points.sort_unstable();
+        points.dedup();
+        points
+    }
+
+    /// Get the points of each cell, see `cell_points`.
+    pub fn cell_point_connectivity(&self) -> Vec<Vec<usize>> {
+        (0..self.cell_faces.len())
+            .map(|cell_id| self.cell_points(cell_id))
+            .collect()
+    }
+
+    /// Get the cells intersected by the plane through `point` with the
+    /// given `normal`, i.e., the cells with points on both sides of it,
+    /// e.g., for slice plots.
+    ///
+    /// Cells merely touching the plane with a point, edge, or face are not
+    /// included.
+    pub fn cells_intersecting_plane(
+        &self,
+        point: Point3<f64>,
+        normal: Vector3<f64>
+    ) -> Vec<usize> {
+        let side: Vec<f64> = self.points.iter()
+            .map(|p| (p - point).dot(&normal))
+            .collect();
+        (0..self.cell_faces.len())
+            .filter(|&cell_id| {
+                let (mut below, mut above) = (false, false);
+                for &face_id in &self.cell_faces[cell_id] {
+                    for &p in &self.faces[idx(face_id)] {
+                        below |= side[idx(p)] < 0.0;
+                        above |= side[idx(p)] > 0.0;
+                    }
                 }
+                below && above
+            })
+            .collect()
+    }
+
+    /// Get the cell with index `cell_id`.
+    pub fn cell(&self, cell_id: usize) -> Option<Cell<'_>> {
+        if cell_id >= self.cell_faces.len() { return None; }
+        Some(Cell { mesh: self, id: cell_id })
+    }
+
+    /// Iterate over all cells in the order of their indices.
+    pub fn cells(&self) -> impl Iterator<Item = Cell<'_>> {
+        (0..self.cell_faces.len()).map(move |id| Cell { mesh: self, id })
+    }
+
+    /// Get the face with index `face_id`.
+    pub fn face(&self, face_id: usize) -> Option<Face<'_>> {
+        if face_id >= self.faces.len() { return None; }
+        Some(Face { mesh: self, id: face_id })
+    }
+
+    /// Iterate over all faces in the order of their indices, i.e., the
+    /// internal faces first.
+    pub fn faces_iter(&self) -> impl Iterator<Item = Face<'_>> {
+        (0..self.faces.len()).map(move |id| Face { mesh: self, id })
+    }
+
+    /// Get the cells containing each point, i.e., the inverse of
+    /// `cell_point_connectivity`.
+    ///
+    /// The cells of each point are sorted by index.
+    pub fn point_cells(&self) -> Vec<Vec<usize>> {
+        let mut point_cells: Vec<Vec<usize>> =
+            vec![Vec::new(); self.points.len()];
+        for cell_id in 0..self.cell_faces.len() {
+            for point_id in self.cell_points(cell_id) {
+                point_cells[point_id].push(cell_id);
+            }
+        }
+        point_cells
+    }
+
+    /// Check if a cell is on a boundary.
+    ///
+    /// Run-time complexity is in O(n), where n is the maximum number of
+    /// neighbors of a cell.
+    /// Returns `false` for unknown cells, and for all cells if the mesh was
+    /// loaded without its connectivity (see `ensure_connectivity`).
+    pub fn is_cell_on_boundary(
+        &self,
+        cell_id: usize,
+        bd_name: Option<String>
+    ) -> bool {
+        let neighbors = match self.cell_neighbors.get(cell_id) {
+            Some(neighbors) => neighbors,
+            None => return false
+        };
+        let mut bid: i64 = 0;
+        if let Some(bd_name) = &bd_name {
+            if let Some(bd) = self.boundary.get(bd_name) {
+                bid = bd.boundary_id;
+            } else {
+                return false;
+            }
+        }
+        neighbors.iter().any(|&neighbor| {
+            if bd_name.is_none() { neighbor < 0 } else { neighbor == bid }
+        })
+    }
+
+    /// Check if a face is a boundary face (in O(1)).
+    pub fn is_face_on_boundary(
+        &self,
+        face_id: usize,
+        bd_name: Option<String>
+    ) -> bool {
+        if face_id >= self.faces.len() { return false; }
+        if let Some(bd_name) = &bd_name {
+            if let Some(bd) = self.boundary.get(bd_name) {
+                self.neighbors[face_id] == bd.boundary_id
+            } else {
+                false
+            }
+        } else {
+            self.neighbors[face_id] < 0
+        }
+    }
+
+    /// Get cell IDs of cells on a given boundary.
+    /// Returns an empty vector if the named boundary does not exist.
+    pub fn boundary_cells(&self, bd_name: &str) -> Vec<usize> {
+        self.boundary_cell_iter(bd_name)
+            .map_or_else(Vec::new, Iterator::collect)
+    }
+
+    /// Get the IDs of the faces of a given boundary, e.g., to look up the
+    /// values of a surface field (see `parse_surface_scalar_field`).
+    /// Returns an empty vector if the named boundary does not exist.
+    pub fn boundary_faces(&self, bd_name: &str) -> Vec<usize> {
+        self.boundary.get(bd_name)
+            .map_or_else(Vec::new, |bd| {
+                (bd.start_face .. bd.start_face + bd.num_faces).collect()
+            })
+    }
+
+    /// Iterate over the IDs of the cells on a given boundary like
+    /// `boundary_cells`, i.e., the owner of each face of the patch, without
+    /// collecting them.
+    /// Returns `None` if the named boundary does not exist.
+    pub fn boundary_cell_iter(
+        &self,
+        bd_name: &str
+    ) -> Option<impl Iterator<Item = usize> + '_> {
+        let bd = self.boundary.get(bd_name)?;
+        Some(self.owners[bd.start_face .. bd.start_face + bd.num_faces]
+            .iter()
+            .map(|&owner| idx(owner)))
+    }
+
+    /// Compute the area vector of the face with index `face_id`.
+    ///
+    /// The (possibly non-planar) face is decomposed into triangles around
+    /// its average point and the triangles' area vectors are summed up.
+    /// Following the OpenFOAM convention, the resulting vector points from
+    /// the owner cell towards the neighbor cell, i.e., out of the domain
+    /// for boundary faces.
+    pub fn face_area_vector(&self, face_id: usize) -> Option<Vector3<f64>> {
+        Some(self.face_center_and_area_vector(face_id)?.1)
+    }
+
+    /// Compute the centroid and area vector of a face by decomposing it
+    /// into triangles around its average point.
+    fn face_center_and_area_vector(
+        &self,
+        face_id: usize
+    ) -> Option<(Point3<f64>, Vector3<f64>)> {
+        let points: Vec<&Point3<f64>> = self.faces.get(face_id)?
+            .iter()
+            .map(|&p| self.points.get(idx(p)))
+            .collect::<Option<Vec<&Point3<f64>>>>()?;
+        if points.len() < 3 { return None; }
+        let center = points.iter()
+            .fold(Vector3::zeros(), |acc, p| acc + p.coords)
+            / points.len() as f64;
+        let center = Point3::from(center);
+        let mut area = Vector3::zeros();
+        let mut weighted_centroids = Vector3::zeros();
+        let mut sum_tri_areas = 0.0;
+        for (i, &p) in points.iter().enumerate() {
+            let next = points[(i + 1) % points.len()];
+            let tri_area = (p - center).cross(&(next - center));
+            let tri_area_mag = tri_area.norm();
+            weighted_centroids += tri_area_mag
+                * (p.coords + next.coords + center.coords) / 3.0;
+            sum_tri_areas += tri_area_mag;
+            area += tri_area;
+        }
+        let centroid = if sum_tri_areas > 0.0 {
+            Point3::from(weighted_centroids / sum_tri_areas)
+        } else {
+            center
+        };
+        Some((centroid, area * 0.5))
+    }
+
+    /// Compute the unit normal of the face with index `face_id`.
+    ///
+    /// Like the area vector, it points from the owner cell towards the
+    /// neighbor cell, i.e., out of the domain for boundary faces.
+    /// Returns `None` for unknown or degenerate faces.
+    pub fn face_normal(&self, face_id: usize) -> Option<Vector3<f64>> {
+        self.face_area_vector(face_id)?.try_normalize(f64::EPSILON)
+    }
+
+    /// Compute the non-orthogonality of each internal face, i.e., the
+    /// angle in degrees between the face normal and the vector connecting
+    /// the owner and neighbor cell centers, like `checkMesh` does.
+    ///
+    /// Uses `cell_centers` if loaded or computed before, and computes the
+    /// cell centers from the mesh geometry otherwise.
+    /// The angle of degenerate faces is NaN.
+    pub fn non_orthogonality(&self) -> Vec<f64> {
+        let centers = self.cell_centers_or_compute();
+        (0..self.num_inner_faces)
+            .map(|face_id| {
+                let normal = match self.face_normal(face_id) {
+                    Some(normal) => normal,
+                    None => return f64::NAN
+                };
+                let delta = centers[self.neighbors[face_id] as usize]
+                    - centers[idx(self.owners[face_id])];
+                let cos = normal.dot(&delta) / delta.norm();
+                cos.clamp(-1.0, 1.0).acos().to_degrees()
+            })
+            .collect()
+    }
+
+    /// Compute the area of the face with index `face_id`.
+    pub fn face_area(&self, face_id: usize) -> Option<f64> {
+        Some(self.face_area_vector(face_id)?.norm())
+    }
+
+    /// Compute the total area of the boundary patch `bd_name`, or `None`
+    /// if there is no such patch.
+    pub fn patch_area(&self, bd_name: &str) -> Option<f64> {
+        let bd = self.boundary.get(bd_name)?;
+        (bd.start_face..bd.start_face + bd.num_faces)
+            .map(|face_id| self.face_area(face_id))
+            .sum()
+    }
+
+    /// Sum the surface field `surface_field`, e.g., the face fluxes `phi`,
+    /// over the faces of the boundary patch `bd_name`.
+    ///
+    /// `surface_field` is indexed by face, i.e., it holds a value for
+    /// each face of the mesh (or at least up to the patch's last face).
+    /// Returns `None` if there is no such patch or `surface_field` is too
+    /// short.
+    pub fn patch_flux(
+        &self,
+        bd_name: &str,
+        surface_field: &[f64]
+    ) -> Option<f64> {
+        let bd = self.boundary.get(bd_name)?;
+        Some(surface_field
+            .get(bd.start_face..bd.start_face + bd.num_faces)?
+            .iter()
+            .sum())
+    }
+
+    /// Integrate the flux of a vector field, e.g., velocities on faces,
+    /// over the boundary patch `bd_name` by summing the dot products with
+    /// the face area vectors.
+    ///
+    /// The flux is positive for vectors pointing out of the domain.
+    /// See `patch_flux` for the layout of `surface_field`.
+    pub fn patch_flux_vector(
+        &self,
+        bd_name: &str,
+        surface_field: &[Vector3<f64>]
+    ) -> Option<f64> {
+        let bd = self.boundary.get(bd_name)?;
+        let faces = bd.start_face..bd.start_face + bd.num_faces;
+        surface_field.get(faces.clone())?
+            .iter()
+            .zip(faces)
+            .map(|(v, face_id)| Some(v.dot(&self.face_area_vector(face_id)?)))
+            .sum()
+    }
+
+    /// Extract the surface mesh of the boundary patch `bd_name`, e.g., for
+    /// exporting a single patch.
+    ///
+    /// Returns the points used by the patch's faces, in the order of their
+    /// first use, and the faces with point indices into these points.
+    /// Returns `None` if there is no such patch.
+    #[allow(clippy::type_complexity)]
+    pub fn extract_patch(
+        &self,
+        bd_name: &str
+    ) -> Option<(Vec<Point3<f64>>, Vec<Vec<usize>>)> {
+        let bd = self.boundary.get(bd_name)?;
+        let mut local_ids: HashMap<usize, usize> = HashMap::new();
+        let mut points: Vec<Point3<f64>> = Vec::new();
+        let faces: Vec<Vec<usize>> = self.faces
+            .get(bd.start_face..bd.start_face + bd.num_faces)?
+            .iter()
+            .map(|face| face.iter()
+                .map(|&point_id| *local_ids.entry(idx(point_id))
+                    .or_insert_with(|| {
+                        points.push(self.points[idx(point_id)]);
+                        points.len() - 1
+                    }))
+                .collect())
+            .collect();
+        Some((points, faces))
+    }
+
+    /// Compute the cell centers from the mesh geometry and store them in
+    /// `cell_centers`.
+    ///
+    /// Like in OpenFOAM, each cell is decomposed into pyramids with its
+    /// faces as bases and the average of its face centers as apex.
+    /// The cell center is the volume-weighted average of the pyramids'
+    /// centroids.
+    /// Unlike `read_cell_centers`, this does not require a `C` file.
+    pub fn compute_cell_centers(&mut self) {
+        self.cell_centers = Some(self.cell_centers_and_volumes().0);
+    }
+
+    /// Compute the volume of each cell from the mesh geometry.
+    pub fn cell_volumes(&self) -> Vec<f64> {
+        self.cell_centers_and_volumes().1
+    }
+
+    /// Integrate a cell-centered scalar field over the domain, i.e., sum up
+    /// the product of each cell's value and volume, e.g., to get the total
+    /// mass from a density field.
+    ///
+    /// Returns `None` if the length of `field` differs from `num_cells`.
+    pub fn volume_integral(&self, field: &[f64]) -> Option<f64> {
+        if field.len() != self.num_cells { return None; }
+        Some(field.iter()
+            .zip(self.cell_volumes())
+            .map(|(val, volume)| val * volume)
+            .sum())
+    }
+
+    /// Compute the volume-weighted average of a cell-centered scalar field,
+    /// i.e., `volume_integral` divided by the total volume.
+    ///
+    /// Returns `None` if the length of `field` differs from `num_cells`.
+    pub fn volume_average(&self, field: &[f64]) -> Option<f64> {
+        let total_volume: f64 = self.cell_volumes().iter().sum();
+        Some(self.volume_integral(field)? / total_volume)
+    }
+
+    /// Compute the Courant number of each cell for the cell-centered
+    /// velocity field `u` and the time step `dt`, e.g., to find the
+    /// maximum for a stability analysis.
+    ///
+    /// Like OpenFOAM's `Co`, this is half the flow through the cell's faces
+    /// during `dt` relative to the cell volume, with the flow approximated
+    /// as `|U| * sum(|S_f|)`, i.e., `dt * |U| * sum(|S_f|) / (2 * V)`.
+    ///
+    /// Returns `None` if the length of `u` differs from `num_cells`.
+    pub fn courant_numbers(
+        &self,
+        u: &[Vector3<f64>],
+        dt: f64
+    ) -> Option<Vec<f64>> {
+        if u.len() != self.num_cells { return None; }
+        let face_areas: Vec<f64> = (0..self.faces.len())
+            .map(|face_id| self.face_area(face_id))
+            .collect::<Option<_>>()?;
+        Some(self.cell_faces.iter()
+            .zip(u)
+            .zip(self.cell_volumes())
+            .map(|((faces, u), volume)| {
+                let area: f64 = faces.iter()
+                    .map(|&face_id| face_areas[idx(face_id)])
+                    .sum();
+                dt * u.norm() * area / (2.0 * volume)
+            })
+            .collect())
+    }
+
+    /// Compute the gradient of a cell-centered scalar field in each cell
+    /// using the Green-Gauss method.
+    ///
+    /// Face values are interpolated linearly between the owner and
+    /// neighbor cell centers; boundary faces take the value of their owner
+    /// cell.
+    /// The cell centers and volumes are computed from the mesh geometry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field` has fewer values than there are cells.
+    pub fn grad_scalar(&self, field: &[f64]) -> Vec<Vector3<f64>> {
+        let (centers, volumes) = self.cell_centers_and_volumes();
+        let mut grad: Vec<Vector3<f64>> =
+            vec![Vector3::zeros(); self.cell_faces.len()];
+        for (owner, neighbor, weight, area) in
+                self.green_gauss_faces(&centers) {
+            match neighbor {
+                Some(neighbor) => {
+                    let face_value = weight * field[owner]
+                        + (1.0 - weight) * field[neighbor];
+                    grad[owner] += face_value * area;
+                    grad[neighbor] -= face_value * area;
+                },
+                None => grad[owner] += field[owner] * area
+            }
+        }
+        for (g, &volume) in grad.iter_mut().zip(&volumes) {
+            if is_nonzero_volume(volume) {
+                *g /= volume;
             }
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Malformed key-value pair in boundary definition: '{}'",
-                    line
-                )
-            ))
         }
-        fn get_parsed_val<T: std::str::FromStr>(
-            line: &str
-        ) -> Result<T, std::io::Error> {
-            match get_val(line)?.parse::<T>() {
-                Ok(val) => {
-                    Ok(val)
+        grad
+    }
+
+    /// Compute the divergence of a cell-centered vector field, e.g., the
+    /// velocity, in each cell using the Green-Gauss method, e.g., to check
+    /// continuity.
+    ///
+    /// Face values are interpolated like in `grad_scalar` and boundary
+    /// faces take the value of their owner cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` has fewer values than there are cells.
+    pub fn divergence(&self, u: &[Vector3<f64>]) -> Vec<f64> {
+        let (centers, volumes) = self.cell_centers_and_volumes();
+        let mut div = vec![0.0; self.cell_faces.len()];
+        for (owner, neighbor, weight, area) in
+                self.green_gauss_faces(&centers) {
+            match neighbor {
+                Some(neighbor) => {
+                    let face_value = weight * u[owner]
+                        + (1.0 - weight) * u[neighbor];
+                    div[owner] += face_value.dot(&area);
+                    div[neighbor] -= face_value.dot(&area);
                 },
-                Err(_) => {
-                    Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        format!("Malformatted boundary data: \"{}\"", line)
-                    ))
-                }
+                None => div[owner] += u[owner].dot(&area)
+            }
+        }
+        for (d, &volume) in div.iter_mut().zip(&volumes) {
+            if is_nonzero_volume(volume) {
+                *d /= volume;
+            }
+        }
+        div
+    }
+
+    /// Compute the curl of a cell-centered vector field in each cell using
+    /// the Green-Gauss method, e.g., the vorticity of the velocity.
+    ///
+    /// The curl is the sum of the cross products of the face area vectors
+    /// with the face values, divided by the cell volume.
+    /// Face values are interpolated like in `grad_scalar` and boundary
+    /// faces take the value of their owner cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `u` has fewer values than there are cells.
+    pub fn curl(&self, u: &[Vector3<f64>]) -> Vec<Vector3<f64>> {
+        let (centers, volumes) = self.cell_centers_and_volumes();
+        let mut curl: Vec<Vector3<f64>> =
+            vec![Vector3::zeros(); self.cell_faces.len()];
+        for (owner, neighbor, weight, area) in
+                self.green_gauss_faces(&centers) {
+            match neighbor {
+                Some(neighbor) => {
+                    let face_value = weight * u[owner]
+                        + (1.0 - weight) * u[neighbor];
+                    curl[owner] += area.cross(&face_value);
+                    curl[neighbor] -= area.cross(&face_value);
+                },
+                None => curl[owner] += area.cross(&u[owner])
+            }
+        }
+        for (c, &volume) in curl.iter_mut().zip(&volumes) {
+            if is_nonzero_volume(volume) {
+                *c /= volume;
+            }
+        }
+        curl
+    }
+
+    /// Iterate over the non-degenerate faces for the Green-Gauss method,
+    /// yielding the owner cell, the neighbor cell (`None` for boundary
+    /// faces), the weight of the owner's value for linear interpolation
+    /// between the cell centers, and the face area vector.
+    fn green_gauss_faces<'a>(
+        &'a self,
+        centers: &'a [Point3<f64>]
+    ) -> impl Iterator<Item = (usize, Option<usize>, f64, Vector3<f64>)> + 'a
+    {
+        (0..self.faces.len()).filter_map(move |face_id| {
+            let (face_center, area) =
+                self.face_center_and_area_vector(face_id)?;
+            let owner = idx(self.owners[face_id]);
+            if !self.face_is_internal(face_id) {
+                return Some((owner, None, 1.0, area));
+            }
+            let neighbor = self.neighbors[face_id] as usize;
+            let owner_dist = (face_center - centers[owner]).norm();
+            let neighbor_dist = (centers[neighbor] - face_center).norm();
+            let weight = neighbor_dist / (owner_dist + neighbor_dist);
+            Some((owner, Some(neighbor), weight, area))
+        })
+    }
+
+    /// Get `cell_centers` if loaded or computed before, and compute the
+    /// cell centers from the mesh geometry otherwise.
+    fn cell_centers_or_compute(&self) -> Cow<'_, [Point3<f64>]> {
+        match &self.cell_centers {
+            Some(centers) => Cow::Borrowed(&centers[..]),
+            None => Cow::Owned(self.cell_centers_and_volumes().0)
+        }
+    }
+
+    /// Build a KD-tree over the cell centers to speed up `nearest_cell`.
+    ///
+    /// Uses `cell_centers` if loaded or computed before, and computes the
+    /// cell centers from the mesh geometry otherwise.
+    /// The index is not updated if `cell_centers` changes later on.
+    #[cfg(feature = "kdtree")]
+    pub fn build_cell_index(&mut self) -> Result<(), FoamError> {
+        let centers = self.cell_centers_or_compute();
+        let mut index = KdTree::new(3);
+        for (cell_id, center) in centers.iter().enumerate() {
+            index.add([center.x, center.y, center.z], cell_id)
+                .map_err(|e| FoamError::InvalidData(format!(
+                    "Cannot index the center of cell {}: {}.", cell_id, e)))?;
+        }
+        self.cell_index = Some(index);
+        Ok(())
+    }
+
+    /// Find the cell whose center is nearest to `p`.
+    ///
+    /// With the `kdtree` feature, this takes O(log n) once the cell index
+    /// has been built by `build_cell_index`. Otherwise, all cell centers
+    /// are searched, taking `cell_centers` if loaded or computed before,
+    /// and computing the cell centers from the mesh geometry otherwise.
+    /// Returns `None` if the mesh has no cells.
+    pub fn nearest_cell(&self, p: &Point3<f64>) -> Option<usize> {
+        #[cfg(feature = "kdtree")]
+        if self.cell_index.is_some() {
+            // The cell centers are not needed:
+            return self.nearest_cell_in(&[], p);
+        }
+        self.nearest_cell_in(&self.cell_centers_or_compute(), p)
+    }
+
+    /// Find the cell nearest to `p` like `nearest_cell`, but searching the
+    /// given cell `centers` if there is no cell index.
+    fn nearest_cell_in(
+        &self,
+        centers: &[Point3<f64>],
+        p: &Point3<f64>
+    ) -> Option<usize> {
+        #[cfg(feature = "kdtree")]
+        if let Some(index) = &self.cell_index {
+            return index.nearest(&[p.x, p.y, p.z], 1, &squared_euclidean)
+                .ok()?
+                .first()
+                .map(|&(_, &cell_id)| cell_id);
+        }
+        centers.iter()
+            .map(|center| (center - p).norm_squared())
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(cell_id, _)| cell_id)
+    }
+
+    /// Compute the distance from each cell center to the nearest face
+    /// center of a boundary patch of type `wall`, e.g., for turbulence
+    /// post-processing.
+    ///
+    /// Uses `cell_centers` if loaded or computed before, and computes the
+    /// cell centers from the mesh geometry otherwise.
+    /// With the `kdtree` feature, the wall face centers are indexed with a
+    /// KD-tree. Otherwise, all of them are searched for each cell.
+    /// All distances are infinite if the mesh has no wall faces.
+    pub fn wall_distance(&self) -> Vec<f64> {
+        let wall_centers: Vec<Point3<f64>> = self.boundary.values()
+            .filter(|bd| bd.kind() == PatchType::Wall)
+            .flat_map(|bd| bd.start_face..bd.start_face + bd.num_faces)
+            .filter_map(|face_id| {
+                Some(self.face_center_and_area_vector(face_id)?.0)
+            })
+            .collect();
+        let centers = self.cell_centers_or_compute();
+
+        #[cfg(feature = "kdtree")]
+        {
+            let mut index: KdTree<f64, (), [f64; 3]> = KdTree::new(3);
+            for c in &wall_centers {
+                // Only non-finite (i.e., degenerate) faces are rejected:
+                index.add([c.x, c.y, c.z], ()).ok();
+            }
+            centers.iter()
+                .map(|p| {
+                    index.nearest(&[p.x, p.y, p.z], 1, &squared_euclidean)
+                        .ok()
+                        .and_then(|nearest| nearest.first().map(|n| n.0))
+                        .map_or(f64::INFINITY, f64::sqrt)
+                })
+                .collect()
+        }
+        #[cfg(not(feature = "kdtree"))]
+        centers.iter()
+            .map(|p| {
+                wall_centers.iter()
+                    .map(|c| (c - p).norm_squared())
+                    .fold(f64::INFINITY, f64::min)
+                    .sqrt()
+            })
+            .collect()
+    }
+
+    /// Interpolate a cell-centered field at the point `p`, similar to
+    /// OpenFOAM's `probes`.
+    ///
+    /// The values of the cell nearest to `p` (see `nearest_cell`) and its
+    /// neighboring cells are weighted with the inverse distance between
+    /// `p` and the respective cell center.
+    /// Returns `None` if the mesh has no cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field` has fewer values than there are cells.
+    pub fn probe_scalar(&self, field: &[f64], p: &Point3<f64>) -> Option<f64> {
+        let centers = self.cell_centers_or_compute();
+        Some(self.probe_weights(&centers, p)?
+            .iter()
+            .map(|&(cell_id, weight)| weight * field[cell_id])
+            .sum())
+    }
+
+    /// Like `probe_scalar`, but for vector fields.
+    pub fn probe_vector(
+        &self,
+        field: &[Vector3<f64>],
+        p: &Point3<f64>
+    ) -> Option<Vector3<f64>> {
+        let centers = self.cell_centers_or_compute();
+        Some(self.probe_weights(&centers, p)?
+            .iter()
+            .map(|&(cell_id, weight)| weight * field[cell_id])
+            .sum())
+    }
+
+    /// Sample a cell-centered field at `n` points evenly spaced along the
+    /// line from `start` to `end` (both included), e.g., for plotting a
+    /// profile.
+    ///
+    /// Each value is interpolated like in `probe_scalar`.
+    /// Returns an empty vector if the mesh has no cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field` has fewer values than there are cells.
+    pub fn sample_line_scalar(
+        &self,
+        field: &[f64],
+        start: Point3<f64>,
+        end: Point3<f64>,
+        n: usize
+    ) -> Vec<(Point3<f64>, f64)> {
+        let centers = self.cell_centers_or_compute();
+        let step = (end - start) / (n.max(2) - 1) as f64;
+        (0..n)
+            .map(|i| start + i as f64 * step)
+            .filter_map(|p| {
+                let value = self.probe_weights(&centers, &p)?
+                    .iter()
+                    .map(|&(cell_id, weight)| weight * field[cell_id])
+                    .sum();
+                Some((p, value))
+            })
+            .collect()
+    }
+
+    /// Get the cells and normalized inverse distance weights for
+    /// interpolating a field at `p`, see `probe_scalar`.
+    fn probe_weights(
+        &self,
+        centers: &[Point3<f64>],
+        p: &Point3<f64>
+    ) -> Option<Vec<(usize, f64)>> {
+        let cell_id = self.nearest_cell_in(centers, p)?;
+        let mut weights: Vec<(usize, f64)> = Vec::new();
+        // Without the connectivity, only the nearest cell is used:
+        let stencil = std::iter::once(cell_id).chain(
+            self.cell_neighbors.get(cell_id)
+                .into_iter()
+                .flatten()
+                .filter(|&&neighbor| neighbor >= 0)
+                .map(|&neighbor| neighbor as usize));
+        for id in stencil {
+            let distance = (centers[id] - p).norm();
+            if distance < f64::EPSILON {
+                return Some(vec![(id, 1.0)]);
+            }
+            weights.push((id, 1.0 / distance));
+        }
+        let sum_weights: f64 = weights.iter().map(|&(_, w)| w).sum();
+        for (_, weight) in &mut weights {
+            *weight /= sum_weights;
+        }
+        Some(weights)
+    }
+
+    /// Interpolate a cell-centered field to the mesh points by weighting
+    /// the values of the cells containing each point with the inverse
+    /// distance between the point and the cell center.
+    ///
+    /// Uses `cell_centers` if loaded or computed before, and computes the
+    /// cell centers from the mesh geometry otherwise.
+    /// The value of points not belonging to any cell is NaN.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_field` has fewer values than there are cells.
+    pub fn interpolate_cell_to_point(&self, cell_field: &[f64]) -> Vec<f64> {
+        let centers = self.cell_centers_or_compute();
+        self.point_cells()
+            .iter()
+            .zip(&self.points)
+            .map(|(cells, point)| {
+                let mut weighted_sum = 0.0;
+                let mut sum_weights = 0.0;
+                for &cell_id in cells {
+                    let distance = (centers[cell_id] - point).norm();
+                    if distance < f64::EPSILON {
+                        return cell_field[cell_id];
+                    }
+                    weighted_sum += cell_field[cell_id] / distance;
+                    sum_weights += 1.0 / distance;
+                }
+                if cells.is_empty() { f64::NAN } else {
+                    weighted_sum / sum_weights
+                }
+            })
+            .collect()
+    }
+
+    /// Compute the centroids and volumes of all cells.
+    fn cell_centers_and_volumes(&self) -> (Vec<Point3<f64>>, Vec<f64>) {
+        let face_geometry: Vec<Option<(Point3<f64>, Vector3<f64>)>> =
+            (0..self.faces.len())
+                .map(|face_id| self.face_center_and_area_vector(face_id))
+                .collect();
+        (0..self.cell_faces.len())
+            .map(|cell_id| self.cell_center_and_volume(
+                cell_id, |face_id| face_geometry[face_id]))
+            .unzip()
+    }
+
+    /// Compute the center and volume of a single cell (see
+    /// `compute_cell_centers`), given a function that returns the center
+    /// and area vector of a face.
+    fn cell_center_and_volume<F>(
+        &self,
+        cell_id: usize,
+        face_geometry: F
+    ) -> (Point3<f64>, f64) where
+            F: Fn(usize) -> Option<(Point3<f64>, Vector3<f64>)> {
+        let geometry: Vec<_> = self.cell_faces[cell_id].iter()
+            .map(|&face_id| idx(face_id))
+            .filter_map(|face_id| Some((face_id, face_geometry(face_id)?)))
+            .collect();
+        if geometry.is_empty() {
+            return (Point3::origin(), 0.0);
+        }
+        let estimate = Point3::from(
+            geometry.iter()
+                .fold(Vector3::zeros(), |acc, (_, (c, _))| acc + c.coords)
+                / geometry.len() as f64
+        );
+        let mut weighted_centroids = Vector3::zeros();
+        let mut volume = 0.0;
+        for (face_id, (face_center, area)) in geometry {
+            // Thrice the pyramid volume; area vectors point away from
+            // the owner cell.
+            let mut pyr_volume = area.dot(&(face_center - estimate));
+            if idx(self.owners[face_id]) != cell_id {
+                pyr_volume = -pyr_volume;
+            }
+            weighted_centroids += pyr_volume
+                * (0.75 * face_center.coords + 0.25 * estimate.coords);
+            volume += pyr_volume;
+        }
+        if is_nonzero_volume(volume) {
+            (Point3::from(weighted_centroids / volume), volume / 3.0)
+        } else {
+            (estimate, volume / 3.0)
+        }
+    }
+
+    /// Write the mesh in ASCII polyMesh format, i.e., the files `points`,
+    /// `faces`, `owner`, `neighbour`, and `boundary`, to the directory
+    /// `dir` (e.g., `constant/polyMesh/` of a case), creating it if
+    /// necessary.
+    ///
+    /// Only the type, size, and start face of each boundary patch are
+    /// written.
+    pub fn write_polymesh<P: AsRef<Path>>(
+        &self,
+        dir: P
+    ) -> Result<(), FoamError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let location = if self.region.is_empty() {
+            String::from("constant/polyMesh")
+        } else {
+            format!("constant/{}/polyMesh", self.region)
+        };
+        let note = format!(
+            "nPoints:{}  nCells:{}  nFaces:{}  nInternalFaces:{}",
+            self.points.len(),
+            self.cell_faces.len(),
+            self.faces.len(),
+            self.num_inner_faces
+        );
+
+        write_foam_list(
+            dir.join("points"), "vectorField", &location, "points", None,
+            &self.points,
+            |w, p| write!(w, "({} {} {})", p.x, p.y, p.z)
+        )?;
+        write_foam_list(
+            dir.join("faces"), "faceList", &location, "faces", None,
+            &self.faces,
+            |w, face| {
+                write!(w, "{}(", face.len())?;
+                for (i, point_id) in face.iter().enumerate() {
+                    if i > 0 { write!(w, " ")?; }
+                    write!(w, "{}", point_id)?;
+                }
+                write!(w, ")")
+            }
+        )?;
+        write_foam_list(
+            dir.join("owner"), "labelList", &location, "owner", Some(&note),
+            &self.owners,
+            |w, owner| write!(w, "{}", owner)
+        )?;
+        write_foam_list(
+            dir.join("neighbour"), "labelList", &location, "neighbour",
+            Some(&note),
+            &self.neighbors[..self.num_inner_faces],
+            |w, neighbor| write!(w, "{}", neighbor)
+        )?;
+
+        let patches = self.boundaries_ordered();
+        let mut w = io::BufWriter::new(
+            std::fs::File::create(dir.join("boundary"))?);
+        write_foam_header(&mut w, "polyBoundaryMesh", Some(&location),
+            "boundary", None)?;
+        writeln!(w, "{}\n(", patches.len())?;
+        for (name, b) in patches {
+            writeln!(w, "    {}\n    {{", name)?;
+            writeln!(w, "        type            {};", b.boundary_type)?;
+            if !b.in_groups.is_empty() {
+                writeln!(w, "        inGroups        {}({});",
+                    b.in_groups.len(), b.in_groups.join(" "))?;
+            }
+            if let Some(neighbour_patch) = &b.neighbour_patch {
+                writeln!(w, "        neighbourPatch  {};", neighbour_patch)?;
+            }
+            writeln!(w, "        nFaces          {};", b.num_faces)?;
+            writeln!(w, "        startFace       {};", b.start_face)?;
+            writeln!(w, "    }}")?;
+        }
+        writeln!(w, ")")?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Write the mesh points and faces to an ASCII legacy VTK file,
+    /// e.g., for inspection in ParaView.
+    ///
+    /// Each face is written as a polygon, with the index of its owner cell
+    /// as cell data (`owner`).
+    pub fn write_vtk_legacy<P: AsRef<Path>>(
+        &self,
+        path: P
+    ) -> Result<(), FoamError> {
+        self.write_vtk(path.as_ref(), None)
+    }
+
+    /// Like `write_vtk_legacy`, but additionally attaches a scalar field
+    /// with one value per cell (e.g., an internal field parsed with
+    /// `parse_scalar_field`) as cell data called `name`.
+    ///
+    /// Each face is assigned the value of its owner cell.
+    pub fn write_vtk_legacy_with_field<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+        field: &[f64]
+    ) -> Result<(), FoamError> {
+        if field.len() != self.cell_faces.len() {
+            return Err(FoamError::CountMismatch {
+                expected: self.cell_faces.len(),
+                found: field.len()
+            });
+        }
+        self.write_vtk(path.as_ref(), Some((name, field)))
+    }
+
+    fn write_vtk(
+        &self,
+        path: &Path,
+        field: Option<(&str, &[f64])>
+    ) -> Result<(), FoamError> {
+        let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(w, "# vtk DataFile Version 3.0")?;
+        writeln!(w, "OpenFOAM mesh {}", self.path.to_string_lossy())?;
+        writeln!(w, "ASCII")?;
+        writeln!(w, "DATASET POLYDATA")?;
+        writeln!(w, "POINTS {} double", self.points.len())?;
+        for p in &self.points {
+            writeln!(w, "{} {} {}", p.x, p.y, p.z)?;
+        }
+        let size: usize = self.faces.iter().map(|f| f.len() + 1).sum();
+        writeln!(w, "POLYGONS {} {}", self.faces.len(), size)?;
+        for face in &self.faces {
+            write!(w, "{}", face.len())?;
+            for point_id in face {
+                write!(w, " {}", point_id)?;
+            }
+            writeln!(w)?;
+        }
+        writeln!(w, "CELL_DATA {}", self.faces.len())?;
+        writeln!(w, "SCALARS owner int 1")?;
+        writeln!(w, "LOOKUP_TABLE default")?;
+        for owner in &self.owners {
+            writeln!(w, "{}", owner)?;
+        }
+        if let Some((name, values)) = field {
+            writeln!(w, "SCALARS {} double 1", name)?;
+            writeln!(w, "LOOKUP_TABLE default")?;
+            for &owner in &self.owners {
+                writeln!(w, "{}", values[idx(owner)])?;
+            }
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Write the boundary patch `bd_name` to an ASCII STL file, e.g., for
+    /// CAD tools.
+    ///
+    /// Each face is split into a fan of triangles around its first point,
+    /// keeping its winding, i.e., a quad yields two triangles.
+    /// All triangles of a face share its normal (see `face_normal`), which
+    /// points out of the domain.
+    pub fn write_stl<P: AsRef<Path>>(
+        &self,
+        bd_name: &str,
+        path: P
+    ) -> Result<(), FoamError> {
+        let (points, faces) = self.extract_patch(bd_name)
+            .ok_or_else(|| FoamError::InvalidData(format!(
+                "There is no boundary patch \"{}\".", bd_name)))?;
+        let start_face = self.boundary[bd_name].start_face;
+        let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(w, "solid {}", bd_name)?;
+        for (i, face) in faces.iter().enumerate() {
+            let n = self.face_normal(start_face + i)
+                .unwrap_or_else(Vector3::zeros);
+            for j in 1..face.len().saturating_sub(1) {
+                writeln!(w, "  facet normal {} {} {}", n.x, n.y, n.z)?;
+                writeln!(w, "    outer loop")?;
+                for &point_id in &[face[0], face[j], face[j + 1]] {
+                    let p = points[point_id];
+                    writeln!(w, "      vertex {} {} {}", p.x, p.y, p.z)?;
+                }
+                writeln!(w, "    endloop")?;
+                writeln!(w, "  endfacet")?;
+            }
+        }
+        writeln!(w, "endsolid {}", bd_name)?;
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Parse scalar values from a given ASCII file.
+    ///
+    /// Expects a file in the following format:
+    /// ```plaintext
+    /// // …
+    ///
+    /// 11360
+    /// (
+    /// 42
+    /// 0
+    /// 3
+    /// // …
+    /// )
+    /// ```
+    ///
+    /// The shorthand for lists of identical values, such as `11360{0}`,
+    /// and short lists on a single line, such as `3(42 0 3)`, are
+    /// supported as well.
+    ///
+    /// The first `skip` lines are ignored. If `skip` is `None`, all lines
+    /// up to the end of the `FoamFile` header are ignored.
+    pub fn parse_scalars<P: AsRef<Path>, T: std::str::FromStr + Send>(
+        filename: P,
+        skip: Option<usize>
+    ) -> Result<Vec<T>, FoamError> {
+        FoamMesh::parse_scalars_with_options(
+            filename, ParseOptions { skip, ..ParseOptions::default() })
+    }
+
+    /// Like `parse_scalars`, but with the given options, e.g., to fail on
+    /// empty lines inside the list.
+    pub fn parse_scalars_with_options<P, T>(
+        filename: P,
+        options: ParseOptions
+    ) -> Result<Vec<T>, FoamError> where
+            P: AsRef<Path>,
+            T: std::str::FromStr + Send {
+        let path = filename.as_ref();
+        FoamMesh::parse_scalars_buffered(open_buffered(path)?, path, options)
+    }
+
+    /// Like `parse_scalars`, but reads from any source such as an
+    /// in-memory buffer.
+    pub fn parse_scalars_reader<R: Read, T: std::str::FromStr + Send>(
+        reader: R,
+        skip: Option<usize>
+    ) -> Result<Vec<T>, FoamError> {
+        FoamMesh::parse_scalars_buffered(
+            buffered(reader)?,
+            Path::new(READER_PATH),
+            ParseOptions { skip, ..ParseOptions::default() }
+        )
+    }
+
+    /// Parse scalars block by block (see `LineReader::read_block`),
+    /// without holding the whole file.
+    fn parse_scalars_buffered<R: BufRead, T: std::str::FromStr + Send>(
+        reader: R,
+        path: &Path,
+        options: ParseOptions
+    ) -> Result<Vec<T>, FoamError> {
+        let mut lines = LineReader::new(reader);
+        lines.skip_header(options.skip)?;
+        if let Some(line) = lines.next_content_line()? {
+            if let Some(data) = parse_repeated_list(
+                    line, |val| val.parse::<T>().ok()) {
+                return Ok(data);
+            }
+            // Short lists may be written on a single line, e.g., `3(0 1 2)`:
+            if let Some(vals) = parse_word_list(line) {
+                let content = String::from(line);
+                return vals.iter()
+                    .map(|val| val.parse::<T>().map_err(|_| {
+                        FoamError::MalformedLine {
+                            file: path.to_path_buf(),
+                            line: lines.line_no,
+                            content: content.clone(),
+                            reason: String::from("Could not parse a scalar.")
+                        }
+                    }))
+                    .collect();
+            }
+            lines.repeat = true;
+        }
+        let num_expected = lines.list_start()?;
+        let mut data: Vec<T> = Vec::with_capacity(num_expected);
+        let mut block = LineBlock::default();
+        let mut more = true;
+        while more {
+            more = lines.read_block(&mut block, |line| {
+                line == ")" || strip_list_end(line).1
+            })?;
+            let chunks = parse_block(&block, |chunk| {
+                let mut data: Vec<T> = Vec::with_capacity(chunk.len());
+                for (line_no, range) in chunk {
+                    let line = &block.text[range.clone()];
+                    let malformed = |reason: &str| FoamError::MalformedLine {
+                        file: path.to_path_buf(),
+                        line: *line_no,
+                        content: String::from(line),
+                        reason: String::from(reason)
+                    };
+                    match line {
+                        "" if options.strict => {
+                            return Err(malformed(EMPTY_LINE));
+                        },
+                        "" | "(" | ")" => continue,
+                        _ => {}
+                    }
+                    match strip_list_end(line).0.parse::<T>() {
+                        Ok(val) => data.push(val),
+                        Err(_) => {
+                            return Err(malformed("Could not parse a scalar."));
+                        }
+                    }
+                }
+                Ok(data)
+            })?;
+            data.extend(chunks.into_iter().flatten());
+        }
+        if data.len() != num_expected {
+            return Err(FoamError::CountMismatch {
+                expected: num_expected,
+                found: data.len()
+            });
+        }
+        Ok(data)
+    }
+
+    /// Parse faces from a given ASCII file.
+    /// Each face is a list of point indices.
+    ///
+    /// Expects a file in the following format:
+    /// ```plaintext
+    /// // …
+    ///
+    /// 11360
+    /// (
+    /// 4(1 42 1723 1682)
+    /// 3(2 3 4)
+    /// // …
+    /// )
+    /// ```
+    ///
+    /// The vertex list of a face may also span multiple lines.
+    ///
+    /// See `parse_scalars` for the meaning of `skip`.
+    pub fn parse_faces<P: AsRef<Path>>(
+        filename: P,
+        skip: Option<usize>
+    ) -> Result<Vec<Vec<usize>>, FoamError> {
+        FoamMesh::parse_faces_with_options(
+            filename, ParseOptions { skip, ..ParseOptions::default() })
+    }
+
+    /// Like `parse_faces`, but with the given options, e.g., to fail on
+    /// lines without point indices inside the list.
+    pub fn parse_faces_with_options<P: AsRef<Path>>(
+        filename: P,
+        options: ParseOptions
+    ) -> Result<Vec<Vec<usize>>, FoamError> {
+        let path = filename.as_ref();
+        FoamMesh::parse_faces_buffered(open_buffered(path)?, path, options)
+    }
+
+    /// Like `parse_faces`, but reads from any source such as an in-memory
+    /// buffer.
+    pub fn parse_faces_reader<R: Read>(
+        reader: R,
+        skip: Option<usize>
+    ) -> Result<Vec<Vec<usize>>, FoamError> {
+        FoamMesh::parse_faces_buffered(
+            buffered(reader)?,
+            Path::new(READER_PATH),
+            ParseOptions { skip, ..ParseOptions::default() }
+        )
+    }
+
+    /// Parse faces with point indices of type `T`, e.g., `Index`, block by
+    /// block (see `LineReader::read_block`), without holding the whole
+    /// file.
+    fn parse_faces_buffered<R: BufRead, T: TryFrom<usize>>(
+        reader: R,
+        path: &Path,
+        options: ParseOptions
+    ) -> Result<Vec<Vec<T>>, FoamError> {
+        let mut lines = LineReader::new(reader);
+        lines.skip_header(options.skip)?;
+        let num_faces_expected = lines.list_start()?;
+        let mut data: Vec<Vec<T>> = Vec::with_capacity(num_faces_expected);
+        FoamMesh::for_each_face(
+            &mut lines, num_faces_expected, path, options.strict, |face| {
+                data.push(face.iter()
+                    .map(|&p| T::try_from(p).ok())
+                    .collect::<Option<Vec<T>>>()?);
+                Some(())
+            })?;
+        Ok(data)
+    }
+
+    /// Call `f` with the point indices of each face of the list starting
+    /// at the current line of `lines`, and check the number of faces.
+    ///
+    /// The lines of each block are scanned concurrently with the
+    /// `parallel` feature, but `f` is called for the faces in order.
+    ///
+    /// `f` returns `None` if a point index exceeds the range of the index
+    /// type it is stored as.
+    fn for_each_face<R: BufRead, F>(
+        lines: &mut LineReader<R>,
+        num_faces_expected: usize,
+        path: &Path,
+        strict: bool,
+        mut f: F
+    ) -> Result<(), FoamError> where
+            F: FnMut(&[usize]) -> Option<()> {
+        let malformed = |line: usize, content: &str, reason: &str| {
+            FoamError::MalformedLine {
+                file: path.to_path_buf(),
+                line,
+                content: String::from(content),
+                reason: String::from(reason)
+            }
+        };
+        const MISMATCH: &str =
+            "Mismatch between number of vertices announced and found.";
+        let mut num_faces: usize = 0;
+        // Numbers of the current line, or of a face spanning multiple
+        // lines:
+        let mut vals: Vec<usize> = Vec::new();
+        // Line number and content of a face spanning multiple lines:
+        let mut partial: Option<(usize, String)> = None;
+        // Depth of brackets, to tell the end of the list from the end of
+        // a face spanning multiple lines:
+        let mut depth: i64 = 0;
+        let mut block = LineBlock::default();
+        let mut more = true;
+        'list: while more {
+            // The end of the list is only known after scanning its lines:
+            more = lines.read_block(&mut block, |_| false)?;
+            let chunks = parse_block(&block, |chunk| {
+                let mut scanned = ScannedChunk {
+                    vals: Vec::with_capacity(chunk.len() * 5),
+                    lines: Vec::with_capacity(chunk.len())
+                };
+                for (_, range) in chunk {
+                    let line = &block.text[range.clone()];
+                    let depth = scan_face_line(line, &mut scanned.vals);
+                    scanned.lines.push((scanned.vals.len(), depth));
+                }
+                Ok(scanned)
+            })?;
+            let mut block_lines = block.lines.iter();
+            for chunk in &chunks {
+                let mut start: usize = 0;
+                for &(end, line_depth) in &chunk.lines {
+                    let (line_no, range) = block_lines.next()
+                        .expect("every line of the block is scanned");
+                    let line = &block.text[range.clone()];
+                    let line_no = *line_no;
+                    let line_vals = &chunk.vals[start..end];
+                    start = end;
+                    depth += line_depth.ok_or_else(|| malformed(line_no, line,
+                        "Point index exceeds the range of usize."))?;
+                    if line_vals.is_empty() {
+                        if depth <= 0 && line.contains(')') { break 'list; }
+                        if strict && line != "(" && line != ")" {
+                            return Err(malformed(line_no, line,
+                                if line.is_empty() { EMPTY_LINE }
+                                else { NO_DATA }));
+                        }
+                        continue;
+                    }
+                    vals.extend_from_slice(line_vals);
+                    let (line_no, content) = partial.take()
+                        .unwrap_or_else(|| (line_no, String::from(line)));
+                    if vals.len() < vals[0] + 1 {
+                        partial = Some((line_no, content));
+                        continue;
+                    }
+                    if vals.len() != vals[0] + 1 {
+                        return Err(malformed(line_no, &content, MISMATCH));
+                    }
+                    f(&vals[1..]).ok_or_else(|| malformed(
+                        line_no, &content,
+                        "Point index exceeds the range of the index type."))?;
+                    num_faces += 1;
+                    vals.clear();
+                }
+            }
+        }
+        if let Some((line_no, content)) = partial {
+            return Err(malformed(line_no, &content, MISMATCH));
+        }
+        if num_faces != num_faces_expected {
+            return Err(FoamError::CountMismatch {
+                expected: num_faces_expected,
+                found: num_faces
+            });
+        }
+        Ok(())
+    }
+
+    /// Parse faces like `parse_faces`, but into the compact `FlatFaces`
+    /// layout, which needs far less memory for large meshes and avoids
+    /// allocating each face separately.
+    ///
+    /// Like `parse_faces`, the file is read line by line.
+    /// Note that `FoamMesh` itself keeps storing its faces in the nested
+    /// layout, see `FoamMesh::flat_faces`.
+    pub fn parse_faces_flat<P: AsRef<Path>>(
+        filename: P,
+        skip: Option<usize>
+    ) -> Result<FlatFaces, FoamError> {
+        let path = filename.as_ref();
+        let mut lines = LineReader::new(open_buffered(path)?);
+        lines.skip_header(skip)?;
+        let num_faces_expected = lines.list_start()?;
+        let mut faces = FlatFaces {
+            offsets: Vec::with_capacity(num_faces_expected + 1),
+            data: Vec::with_capacity(4 * num_faces_expected)
+        };
+        faces.offsets.push(0);
+        FoamMesh::for_each_face(
+            &mut lines, num_faces_expected, path, false, |face| {
+                for &p in face {
+                    faces.data.push(u32::try_from(p).ok()?);
+                }
+                faces.offsets.push(faces.data.len());
+                Some(())
+            })?;
+        Ok(faces)
+    }
+
+    /// Parse mesh point data from a given ASCII file.
+    ///
+    /// Expects a file in the following format:
+    /// ```plaintext
+    /// // …
+    ///
+    /// 5043
+    /// (
+    /// (42 0 1)
+    /// (3 2.001 13.37)
+    /// // …
+    /// )
+    /// ```
+    ///
+    /// See `parse_scalars` for the meaning of `skip`.
+    pub fn parse_points<P: AsRef<Path>>(
+        filename: P,
+        skip: Option<usize>
+    ) -> Result<Vec<Point3<f64>>, FoamError> {
+        FoamMesh::parse_points_with_options(
+            filename, ParseOptions { skip, ..ParseOptions::default() })
+    }
+
+    /// Like `parse_points`, but with the given options, e.g., to fail on
+    /// lines without a point inside the list.
+    pub fn parse_points_with_options<P: AsRef<Path>>(
+        filename: P,
+        options: ParseOptions
+    ) -> Result<Vec<Point3<f64>>, FoamError> {
+        let path = filename.as_ref();
+        FoamMesh::parse_points_str(&read_to_string(path)?, path, options)
+    }
+
+    /// Like `parse_points`, but reads from any source such as an in-memory
+    /// buffer.
+    pub fn parse_points_reader<R: Read>(
+        reader: R,
+        skip: Option<usize>
+    ) -> Result<Vec<Point3<f64>>, FoamError> {
+        FoamMesh::parse_points_str(
+            &read_reader_to_string(reader, READER_PATH)?,
+            Path::new(READER_PATH),
+            ParseOptions { skip, ..ParseOptions::default() }
+        )
+    }
+
+    fn parse_points_str(
+        content: &str,
+        path: &Path,
+        options: ParseOptions
+    ) -> Result<Vec<Point3<f64>>, FoamError> {
+        let lines: Vec<&str> = content.split('\n').collect();
+        let (num_points_expected, mut first) = find_list_start(
+            &lines, lines_to_skip(content, options.skip));
+        let mut data_lines: &[&str] = &lines[first..];
+        if options.strict {
+            // Only check the lines between the brackets of the list:
+            if data_lines.first().is_some_and(|line| line.trim() == "(") {
+                first += 1;
+                data_lines = &data_lines[1..];
+            }
+            let end = data_lines.iter()
+                .enumerate()
+                .find_map(|(i, line)| match strip_list_end(line) {
+                    (")", _) => Some(i),
+                    (_, true) => Some(i + 1),
+                    _ => None
+                })
+                .unwrap_or(data_lines.len());
+            data_lines = &data_lines[..end];
+        }
+        let data: Vec<Point3<f64>> = parse_lines(data_lines, |i, line| {
+            let line = strip_list_end(line).0;
+            if !line.starts_with('(') || !line.ends_with(')') {
+                if !options.strict { return Ok(None); }
+                return Err(FoamError::MalformedLine {
+                    file: path.to_path_buf(),
+                    line: first + i + 1,
+                    content: String::from(line),
+                    reason: String::from(
+                        if line.is_empty() { EMPTY_LINE } else { NO_DATA })
+                });
+            }
+            match parse_point3(line) {
+                Some(v) => Ok(Some(v)),
+                None => Err(FoamError::MalformedLine {
+                    file: path.to_path_buf(),
+                    line: first + i + 1,
+                    content: String::from(line),
+                    reason: String::from("Could not parse three floats.")
+                })
+            }
+        })?;
+        if data.len() != num_points_expected {
+            return Err(FoamError::CountMismatch {
+                expected: num_points_expected,
+                found: data.len()
+            });
+        }
+        Ok(data)
+    }
+
+    /// Parse an OpenFOAM boundary definition file.
+    ///
+    /// Expects a file in the following format:
+    /// ```plaintext
+    /// // …
+    ///
+    /// 3
+    /// (
+    ///     inlet
+    ///     {
+    ///         type            patch;
+    ///         nFaces          605;
+    ///         startFace       971201;
+    ///     }
+    ///     outlet
+    ///     {
+    ///         type            patch;
+    ///         nFaces          605;
+    ///         startFace       971806;
+    ///     }
+    ///     walls
+    ///     {
+    ///         type            patch;
+    ///         nFaces          23848;
+    ///         startFace       972411;
+    ///     }
+    /// )
+    /// ```
+    ///
+    /// The layout of whitespace and line breaks does not matter, e.g., the
+    /// opening brace of a patch may follow its name on the same line.
+    /// Entries other than `type`, `nFaces`, `startFace`, and `inGroups`
+    /// are ignored.
+    ///
+    /// See `parse_scalars` for the meaning of `skip`.
+    pub fn parse_boundary<P: AsRef<Path>>(
+        filename: P,
+        skip: Option<usize>
+    ) -> Result<HashMap<String, Boundary>, FoamError> {
+        FoamMesh::parse_boundary_str(&read_to_string(&filename)?, skip)
+    }
+
+    /// Like `parse_boundary`, but reads from any source such as an
+    /// in-memory buffer.
+    pub fn parse_boundary_reader<R: Read>(
+        reader: R,
+        skip: Option<usize>
+    ) -> Result<HashMap<String, Boundary>, FoamError> {
+        FoamMesh::parse_boundary_str(
+            &read_reader_to_string(reader, READER_PATH)?, skip)
+    }
+
+    fn parse_boundary_str(
+        content: &str,
+        skip: Option<usize>
+    ) -> Result<HashMap<String, Boundary>, FoamError> {
+        let skip = lines_to_skip(content, skip);
+        let start = match skip {
+            0 => 0,
+            _ => content.match_indices('\n')
+                .nth(skip - 1)
+                .map_or(content.len(), |(i, _)| i + 1)
+        };
+        let content = &content[start..];
+        let tokens = tokenize_dict(content);
+        let list_start = tokens.windows(2)
+            .position(|t| t[0].1.parse::<usize>().is_ok() && t[1].1 == "(")
+            .ok_or_else(|| FoamError::MissingBracket {
+                bracket: '(',
+                context: String::from("after number of boundaries")
+            })?;
+        let num_expected: usize = tokens[list_start].1.parse().unwrap();
+
+        let mut bd: HashMap<String, Boundary> = HashMap::new();
+        let mut bid: i64 = 0; // TODO: can this really be <0?
+        let mut tokens = tokens[list_start + 2..].iter();
+        loop {
+            let name = match tokens.next() {
+                None => return Err(FoamError::MissingBracket {
+                    bracket: ')',
+                    context: String::from(
+                        "at the end of the boundary definition"
+                    )
+                }),
+                Some((_, ")")) => break,
+                Some((_, name)) => name.trim_matches('"')
+            };
+            if tokens.next().map(|t| t.1) != Some("{") {
+                return Err(FoamError::MissingBracket {
+                    bracket: '{',
+                    context: format!("after boundary patch \"{}\"", name)
+                });
+            }
+            let entries = parse_dict_entries(content, &mut tokens, name)?;
+            let get_val = |key: &str| -> Result<&str, FoamError> {
+                entries.get(key).copied().ok_or_else(|| {
+                    FoamError::InvalidData(format!(
+                        "Missing '{}' entry in boundary patch \"{}\".",
+                        key,
+                        name
+                    ))
+                })
+            };
+            let get_parsed_val = |key: &str| -> Result<usize, FoamError> {
+                let val = get_val(key)?;
+                val.parse::<usize>().map_err(|_| {
+                    FoamError::InvalidData(format!(
+                        "Malformatted boundary data: \"{} {};\"",
+                        key,
+                        val
+                    ))
+                })
+            };
+            let in_groups = match entries.get("inGroups") {
+                None => Vec::new(),
+                Some(val) => parse_word_list(val).ok_or_else(|| {
+                    FoamError::InvalidData(format!(
+                        "Malformatted boundary data: \"inGroups {};\"",
+                        val
+                    ))
+                })?
+            };
+            bd.insert(String::from(name), Boundary {
+                boundary_type: String::from(get_val("type")?),
+                num_faces: get_parsed_val("nFaces")?,
+                start_face: get_parsed_val("startFace")?,
+                boundary_id: -10-bid, // TODO: why? In Python impl, _set_boundary_faces, -10 seems to be default neighbor for boundaries…
+                in_groups,
+                neighbour_patch: entries.get("neighbourPatch")
+                    .map(|val| String::from(val.trim_matches('"')))
+            });
+            bid += 1;
+        }
+        if bd.len() != num_expected {
+            return Err(FoamError::CountMismatch {
+                expected: num_expected,
+                found: bd.len()
+            });
+        }
+
+        Ok(bd)
+    }
+}
+
+/// Get the faces and neighbors of each cell (see `FoamMesh`) from the
+/// owners and neighbors of the faces.
+///
+/// The number of faces must fit into `Index`.
+fn construct_cells(
+    owners: &[Index],
+    neighbors: &[i64],
+    num_cells: usize
+) -> (Vec<Vec<Index>>, Vec<Vec<i64>>) {
+    let mut cell_faces: Vec<Vec<Index>> = vec![Vec::new(); num_cells];
+    let mut cell_neighbors: Vec<Vec<i64>> = vec![Vec::new(); num_cells];
+    for (i, &owner) in owners.iter().enumerate() {
+        cell_faces[idx(owner)].push(i as Index);
+    }
+    for (i, &neighbor) in neighbors.iter().enumerate() {
+        let owner = idx(owners[i]);
+        if neighbor >= 0 {
+            cell_faces[neighbor as usize].push(i as Index);
+            cell_neighbors[neighbor as usize].push(owner as i64);
+        }
+        cell_neighbors[owner].push(neighbor);
+    }
+    (cell_faces, cell_neighbors)
+}
+
+/// Check that the boundary patches only consist of the faces following
+/// the internal faces.
+fn check_boundary_faces(
+    boundary: &HashMap<String, Boundary>,
+    num_inner_faces: usize,
+    num_faces: usize
+) -> Result<(), FoamError> {
+    if num_inner_faces > num_faces {
+        return Err(FoamError::InvalidData(format!(
+            "The mesh has {} internal faces, but only {} faces in total.",
+            num_inner_faces, num_faces
+        )));
+    }
+    for (name, b) in boundary {
+        if b.start_face < num_inner_faces
+                || b.start_face + b.num_faces > num_faces {
+            return Err(FoamError::InvalidData(format!(
+                "Boundary patch \"{}\" consists of faces {}..{}, but \
+                only faces {}..{} are boundary faces.",
+                name,
+                b.start_face,
+                b.start_face + b.num_faces,
+                num_inner_faces,
+                num_faces
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Faces stored in compressed sparse row (CSR) format, i.e., the point
+/// indices of all faces in one vector.
+///
+/// The points of face `i` are `data[offsets[i]..offsets[i + 1]]`.
+/// This layout is only used by `FoamMesh::parse_faces_flat` and
+/// `FoamMesh::flat_faces`; `FoamMesh` stores its faces as nested vectors.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlatFaces {
+    pub offsets: Vec<usize>,
+    pub data: Vec<u32>,
+}
+
+impl FlatFaces {
+    /// Get the point indices of the face with index `face_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such face.
+    pub fn face_vertices(&self, face_id: usize) -> &[u32] {
+        &self.data[self.offsets[face_id]..self.offsets[face_id + 1]]
+    }
+
+    /// Number of faces.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the point indices of all faces.
+    pub fn iter(&self) -> impl Iterator<Item = &[u32]> {
+        self.offsets.windows(2).map(move |o| &self.data[o[0]..o[1]])
+    }
+}
+
+impl TryFrom<&[Vec<usize>]> for FlatFaces {
+    type Error = FoamError;
+
+    /// Convert faces such as `FoamMesh::faces`, failing if a point index
+    /// does not fit into a `u32`.
+    fn try_from(faces: &[Vec<usize>]) -> Result<Self, Self::Error> {
+        flatten_faces(faces)
+    }
+}
+
+/// Copy nested faces with point indices of type `T`, e.g., `Index`, into
+/// the `FlatFaces` layout, failing if a point index does not fit into a
+/// `u32`.
+fn flatten_faces<T>(faces: &[Vec<T>]) -> Result<FlatFaces, FoamError> where
+        T: Copy + std::convert::TryInto<u32> + std::fmt::Display {
+    let mut flat = FlatFaces {
+        offsets: Vec::with_capacity(faces.len() + 1),
+        data: Vec::with_capacity(faces.iter().map(Vec::len).sum())
+    };
+    flat.offsets.push(0);
+    for face in faces {
+        for &p in face {
+            flat.data.push(p.try_into().map_err(|_| {
+                FoamError::InvalidData(format!(
+                    "Point index {} exceeds the range of u32.",
+                    p
+                ))
+            })?);
+        }
+        flat.offsets.push(flat.data.len());
+    }
+    Ok(flat)
+}
+
+/// Point indices of the lines of a chunk scanned by `scan_face_line`.
+struct ScannedChunk {
+    vals: Vec<usize>,
+    /// End of the indices of each line in `vals`, and its change in
+    /// bracket depth, or `None` if an integer overflows.
+    lines: Vec<(usize, Option<i64>)>,
+}
+
+/// Append all unsigned integers on a line of a face list to `vals`, and
+/// return the change in bracket depth, or `None` if an integer overflows.
+fn scan_face_line(line: &str, vals: &mut Vec<usize>) -> Option<i64> {
+    let mut depth: i64 = 0;
+    let mut num: Option<usize> = None;
+    for &b in line.as_bytes() {
+        if b.is_ascii_digit() {
+            let digit = (b - b'0') as usize;
+            num = Some(num.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+            continue;
+        }
+        if let Some(n) = num.take() { vals.push(n); }
+        match b {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ => {}
+        }
+    }
+    if let Some(n) = num { vals.push(n); }
+    Some(depth)
+}
+
+/// A cell of a `FoamMesh`, giving access to its connectivity and
+/// geometry.
+#[derive(Clone, Copy)]
+pub struct Cell<'a> {
+    mesh: &'a FoamMesh,
+    id: usize,
+}
+
+impl<'a> Cell<'a> {
+    /// Index of the cell in the mesh.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Indices of the cell's faces.
+    pub fn faces(&self) -> &'a [Index] {
+        &self.mesh.cell_faces[self.id]
+    }
+
+    /// Indices of the neighboring cells, with the (negative)
+    /// `boundary_id` in place of a cell for each boundary face.
+    pub fn neighbors(&self) -> &'a [i64] {
+        &self.mesh.cell_neighbors[self.id]
+    }
+
+    /// Indices of the cell's points, see `FoamMesh::cell_points`.
+    pub fn points(&self) -> Vec<usize> {
+        self.mesh.cell_points(self.id)
+    }
+
+    /// The cell center from `FoamMesh::cell_centers` if loaded or computed
+    /// before, and computed from the mesh geometry otherwise.
+    pub fn center(&self) -> Point3<f64> {
+        match &self.mesh.cell_centers {
+            Some(centers) => centers[self.id],
+            None => self.geometry().0
+        }
+    }
+
+    /// Compute the cell volume from the mesh geometry.
+    pub fn volume(&self) -> f64 {
+        self.geometry().1
+    }
+
+    fn geometry(&self) -> (Point3<f64>, f64) {
+        self.mesh.cell_center_and_volume(
+            self.id,
+            |face_id| self.mesh.face_center_and_area_vector(face_id)
+        )
+    }
+}
+
+/// A face of a `FoamMesh`, giving access to its connectivity and
+/// geometry.
+#[derive(Clone, Copy)]
+pub struct Face<'a> {
+    mesh: &'a FoamMesh,
+    id: usize,
+}
+
+impl<'a> Face<'a> {
+    /// Index of the face in the mesh.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Indices of the face's points.
+    pub fn points(&self) -> &'a [Index] {
+        &self.mesh.faces[self.id]
+    }
+
+    /// Index of the owner cell.
+    pub fn owner(&self) -> usize {
+        idx(self.mesh.owners[self.id])
+    }
+
+    /// Index of the neighbor cell, or `None` for boundary faces.
+    pub fn neighbor(&self) -> Option<usize> {
+        if self.is_boundary() { return None; }
+        Some(self.mesh.neighbors[self.id] as usize)
+    }
+
+    /// Check if the face is on a boundary, i.e., it has no neighbor cell.
+    pub fn is_boundary(&self) -> bool {
+        !self.mesh.face_is_internal(self.id)
+    }
+
+    /// See `FoamMesh::face_area`.
+    pub fn area(&self) -> Option<f64> {
+        self.mesh.face_area(self.id)
+    }
+
+    /// See `FoamMesh::face_normal`.
+    pub fn normal(&self) -> Option<Vector3<f64>> {
+        self.mesh.face_normal(self.id)
+    }
+
+    /// Compute the centroid of the face, or `None` if it is degenerate.
+    pub fn center(&self) -> Option<Point3<f64>> {
+        Some(self.mesh.face_center_and_area_vector(self.id)?.0)
+    }
+}
+
+/// Split a dictionary into words, quoted strings, and the punctuation
+/// characters `{`, `}`, `(`, `)`, `[`, `]`, and `;`.
+///
+/// Each token is returned with its byte offset in `content`.
+fn tokenize_dict(content: &str) -> Vec<(usize, &str)> {
+    let bytes = content.as_bytes();
+    let is_punctuation = |b: u8| b"{}()[];\"".contains(&b);
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            },
+            b'"' => {
+                i = content[i + 1..].find('"')
+                    .map_or(bytes.len(), |n| i + n + 2);
+            },
+            b if is_punctuation(b) => i += 1,
+            _ => {
+                while i < bytes.len()
+                        && !bytes[i].is_ascii_whitespace()
+                        && !is_punctuation(bytes[i]) {
+                    i += 1;
+                }
+            }
+        }
+        tokens.push((start, &content[start..i]));
+    }
+    tokens
+}
+
+/// Read the `key value;` entries of a dictionary from `tokens` (see
+/// `tokenize_dict`) up to and including its closing brace.
+///
+/// Values are returned as they appear in `content`, e.g., `1(wall)`.
+/// Nested dictionaries are skipped.
+fn parse_dict_entries<'a>(
+    content: &'a str,
+    tokens: &mut std::slice::Iter<'_, (usize, &'a str)>,
+    dict_name: &str
+) -> Result<HashMap<&'a str, &'a str>, FoamError> {
+    let missing_brace = || FoamError::MissingBracket {
+        bracket: '}',
+        context: format!("at the end of \"{}\"", dict_name)
+    };
+    let mut entries: HashMap<&str, &str> = HashMap::new();
+    loop {
+        let (key_offset, key) = match tokens.next() {
+            None => return Err(missing_brace()),
+            Some((_, "}")) => return Ok(entries),
+            Some(&(offset, key)) => (offset, key)
+        };
+        if key.starts_with('#') {
+            // Skip the rest of the directive's line, like `parse_dict_tree`:
+            let line_end = content[key_offset..].find('\n')
+                .map_or(content.len(), |n| key_offset + n);
+            while let Some(&(offset, _)) = tokens.as_slice().first() {
+                if offset >= line_end { break; }
+                tokens.next();
+            }
+            continue;
+        }
+        let mut value: Option<(usize, usize)> = None;
+        let mut depth: usize = 0;
+        loop {
+            let &(offset, token) = tokens.next().ok_or_else(missing_brace)?;
+            match token {
+                "{" if depth == 0 && value.is_none() => {
+                    parse_dict_entries(content, tokens, key)?;
+                    break;
+                },
+                ";" if depth == 0 => break,
+                "}" if depth == 0 => return Err(FoamError::InvalidData(
+                    format!("Missing ';' after \"{}\" in \"{}\".",
+                        key, dict_name)
+                )),
+                "(" => depth += 1,
+                ")" => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            let start = value.map_or(offset, |(start, _)| start);
+            value = Some((start, offset + token.len()));
+        }
+        let value = value.map_or("", |(start, end)| &content[start..end]);
+        entries.insert(key, value);
+    }
+}
+
+type DictTokens<'a, 'b> =
+    std::iter::Peekable<std::slice::Iter<'b, (usize, &'a str)>>;
+
+/// Read the entries of a dictionary from `tokens` (see `tokenize_dict`)
+/// up to and including its closing brace, or up to the end of `tokens`
+/// for the top level (`dict_name` is `None`).
+fn parse_dict_tree(
+    content: &str,
+    tokens: &mut DictTokens,
+    dict_name: Option<&str>
+) -> Result<HashMap<String, FoamDict>, FoamError> {
+    let context = dict_name.unwrap_or("top level");
+    let mut entries: HashMap<String, FoamDict> = HashMap::new();
+    loop {
+        let &(offset, key) = match tokens.next() {
+            None if dict_name.is_none() => return Ok(entries),
+            None => return Err(FoamError::MissingBracket {
+                bracket: '}',
+                context: format!("at the end of \"{}\"", context)
+            }),
+            Some((_, "}")) if dict_name.is_some() => return Ok(entries),
+            Some(token) => token
+        };
+        if key.starts_with('#') {
+            // Skip the rest of the directive's line:
+            let line_end = content[offset..].find('\n')
+                .map_or(content.len(), |n| offset + n);
+            while tokens.next_if(|&&(o, _)| o < line_end).is_some() {}
+            continue;
+        }
+        if matches!(key, "{" | "}" | "(" | ")" | "[" | "]" | ";") {
+            return Err(FoamError::InvalidData(format!(
+                "Unexpected '{}' in \"{}\".", key, context)));
+        }
+        let key = key.trim_matches('"').to_string();
+        let value = if tokens.next_if(|&&(_, t)| t == "{").is_some() {
+            FoamDict::Dict(parse_dict_tree(content, tokens, Some(&key))?)
+        } else {
+            let mut values = parse_dict_values(content, tokens, &key, ";")?;
+            match values.len() {
+                1 => values.pop().unwrap(),
+                _ => FoamDict::List(values)
+            }
+        };
+        entries.insert(key, value);
+    }
+}
+
+/// Read values from `tokens` up to and including the token `end`, i.e.,
+/// `;` for an entry, or `)` or `]` for a list.
+fn parse_dict_values(
+    content: &str,
+    tokens: &mut DictTokens,
+    context: &str,
+    end: &str
+) -> Result<Vec<FoamDict>, FoamError> {
+    let mut values: Vec<FoamDict> = Vec::new();
+    loop {
+        let &(offset, token) = match tokens.next() {
+            Some(token) => token,
+            None if end == ";" => return Err(FoamError::InvalidData(
+                format!("Missing ';' after \"{}\".", context))),
+            None => return Err(FoamError::MissingBracket {
+                bracket: end.chars().next().unwrap(),
+                context: format!("in \"{}\"", context)
+            })
+        };
+        let value = match token {
+            t if t == end => return Ok(values),
+            "(" | "[" => FoamDict::List(parse_dict_values(
+                content,
+                tokens,
+                context,
+                if token == "(" { ")" } else { "]" }
+            )?),
+            "{" => FoamDict::Dict(
+                parse_dict_tree(content, tokens, Some(context))?),
+            ";" | ")" | "]" | "}" => return Err(FoamError::InvalidData(
+                format!("Unexpected '{}' in \"{}\".", token, context))),
+            _ => {
+                // Drop the size prefix of a list such as `2(0 1)`:
+                let is_size = token.parse::<usize>().is_ok()
+                    && tokens.peek().is_some_and(|&&(o, t)| {
+                        t == "(" && o == offset + token.len()
+                    });
+                if is_size {
+                    continue;
+                }
+                match token.as_bytes()[0] {
+                    b'0'..=b'9' | b'-' | b'+' | b'.' => token.parse::<f64>()
+                        .map_or_else(
+                            |_| FoamDict::Word(String::from(token)),
+                            FoamDict::Scalar),
+                    _ => FoamDict::Word(
+                        String::from(token.trim_matches('"')))
+                }
+            }
+        };
+        values.push(value);
+    }
+}
+
+/// Parse an internal field such as a cell centers file.
+///
+/// Expects a closure `parse_fn` to parse a single value to
+/// the desired type (e.g., "(0.1 0 3.3)" to a Vector3).
+///
+/// If the internal field is declared 'nonuniform' and consists of
+/// several consecutive lists, their values are concatenated.
+/// See `parse_internal_field_sections` for reading only the first one.
+///
+/// If the internal field is declared 'uniform',
+/// only the first data line will be read.
+///
+/// Files included by `#include "file"` are spliced in before parsing,
+/// looking them up next to `filename` and in the case's `system` and
+/// `constant` directories.
+///
+/// Files declared as `format binary;` in their header are supported
+/// as well. In that case, each binary value is formatted as text
+/// (e.g., "(0.1 0 3.3)" for vectors) before being handed to `parse_fn`,
+/// such that the same closure can be used for both formats.
+pub fn parse_internal_field<T, P, F>(
+    filename: P,
+    parse_fn: F
+) -> Result<Vec<T>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    parse_internal_field_sections(filename, parse_fn, true)
+}
+
+/// Like `parse_internal_field`, but if `all_sections` is `false`,
+/// only the first section of a 'nonuniform' internal field is read.
+/// The latter is compatible with the reference Python implementation.
+///
+/// Text files are read line by line, without holding the whole file.
+pub fn parse_internal_field_sections<T, P, F>(
+    filename: P,
+    parse_fn: F,
+    all_sections: bool
+) -> Result<Vec<T>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    let path = filename.as_ref();
+    let mut data: Vec<T> = Vec::new();
+    let streamed = stream_internal_field(
+        path, &parse_fn, all_sections, |val| data.push(val))?;
+    if streamed.is_some() {
+        return Ok(data);
+    }
+    parse_internal_field_bytes(read_bytes(path)?, path, parse_fn, all_sections)
+}
+
+/// Like `parse_internal_field`, but reads from any source such as an
+/// in-memory buffer.
+pub fn parse_internal_field_reader<T, R, F>(
+    mut reader: R,
+    parse_fn: F
+) -> Result<Vec<T>, FoamError> where
+        R: Read,
+        F: Fn(&str) -> Option<T> {
+    let mut bytes: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    parse_internal_field_bytes(
+        decompress(bytes)?, Path::new(READER_PATH), parse_fn, true)
+}
+
+/// Call `f` with each value of the internal field of a file, in order,
+/// without collecting them.
+///
+/// Unlike `parse_internal_field`, a 'nonuniform' internal field is not
+/// collected, such that huge fields can be reduced (e.g., summed up)
+/// without holding all values in memory.
+/// Binary files and files with `#include` directives before the internal
+/// field are read completely before their values are passed on.
+/// Returns the number of values.
+pub fn for_each_internal_field_value<T, P, F, G>(
+    filename: P,
+    parse_fn: F,
+    mut f: G
+) -> Result<usize, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T>,
+        G: FnMut(T) {
+    let path = filename.as_ref();
+    let streamed = stream_internal_field(path, &parse_fn, true, &mut f)?;
+    if let Some(num_vals) = streamed {
+        return Ok(num_vals);
+    }
+    let data = parse_internal_field(path, parse_fn)?;
+    let num_vals = data.len();
+    data.into_iter().for_each(f);
+    Ok(num_vals)
+}
+
+/// Read the internal field of a text file line by line, calling `f` with
+/// each value, see `parse_internal_field_sections`.
+///
+/// Returns the number of values, or `None` without calling `f` if the
+/// file is binary or includes other files before the internal field,
+/// i.e., needs to be read completely.
+fn stream_internal_field<T, F, G>(
+    path: &Path,
+    parse_fn: F,
+    all_sections: bool,
+    mut f: G
+) -> Result<Option<usize>, FoamError> where
+        F: Fn(&str) -> Option<T>,
+        G: FnMut(T) {
+    let mut lines = LineReader::new(open_buffered(path)?);
+    let malformed = |line_no: usize, line: &str, reason: &str| {
+        FoamError::MalformedLine {
+            file: path.to_path_buf(),
+            line: line_no,
+            content: String::from(line),
+            reason: String::from(reason)
+        }
+    };
+    let line = loop {
+        match lines.next_line()? {
+            None => return Err(FoamError::InvalidData(String::from(
+                "Did not find any data in internal field file."
+            ))),
+            Some(line) if line.starts_with("format")
+                    && line.contains("binary") => return Ok(None),
+            Some(line) if line.starts_with("#include") => return Ok(None),
+            Some(line) if line.starts_with("internalField") => {
+                break String::from(line);
+            },
+            Some(_) => {}
+        }
+    };
+
+    let (vals, is_list) = if let Some(list) = line.split('>').nth(1)
+            .filter(|list| list.contains('{')) {
+        (parse_repeated_list(list, &parse_fn), true)
+    } else if line.contains("nonuniform") {
+        (None, false)
+    } else if line.contains("uniform") {
+        (parse_internal_field_data_uniform(&line, &parse_fn).ok(), true)
+    } else {
+        return Err(FoamError::InvalidData(String::from(
+            "Malformed internal field file: Not defined as either \
+            uniform of nonuniform."
+        )));
+    };
+    if is_list {
+        let vals = vals.ok_or_else(|| malformed(
+            lines.line_no, &line, "Could not parse the internal field."))?;
+        let num_vals = vals.len();
+        vals.into_iter().for_each(f);
+        return Ok(Some(num_vals));
+    }
+
+    let mut num_vals: usize = 0;
+    let mut num_expected = match lines.next_line()? {
+        Some(line) => match parse_repeated_list(line, &parse_fn) {
+            Some(vals) => {
+                let num_vals = vals.len();
+                vals.into_iter().for_each(f);
+                return Ok(Some(num_vals));
+            },
+            None => line.parse::<usize>().ok()
+        },
+        None => None
+    }.ok_or_else(|| FoamError::InvalidData(String::from(
+        "Malformed internal field file: Number of expected values not \
+        given."
+    )))?;
+    loop {
+        if lines.next_line()? != Some("(") {
+            return Err(FoamError::MissingBracket {
+                bracket: '(',
+                context: String::from("after number of values")
+            });
+        }
+        let shorter = || FoamError::InvalidData(String::from(
+            "Internal field file is shorter than declared."
+        ));
+        let mut is_list_end = false;
+        for _ in 0..num_expected {
+            if is_list_end { return Err(shorter()); }
+            let line_no = lines.line_no + 1;
+            let line = lines.next_line()?.ok_or_else(shorter)?;
+            let (val, is_end) = strip_list_end(line);
+            let val = parse_fn(val).ok_or_else(|| malformed(
+                line_no, line, "Could not parse a value."))?;
+            f(val);
+            is_list_end = is_end;
+        }
+        num_vals += num_expected;
+        if !is_list_end && lines.next_line()? != Some(")") {
+            return Err(FoamError::MissingBracket {
+                bracket: ')',
+                context: String::from("after the internal field values")
+            });
+        }
+        if !all_sections {
+            return Ok(Some(num_vals));
+        }
+        // Further sections, see `parse_internal_field`:
+        match lines.next_line()?.map(str::parse::<usize>) {
+            Some(Ok(n)) => num_expected = n,
+            _ => return Ok(Some(num_vals))
+        }
+    }
+}
+
+/// Reads lines one by one, trimming whitespace and removing comments
+/// like `strip_comments`.
+struct LineReader<R: BufRead> {
+    reader: R,
+    line: String,
+    /// The current line without comments.
+    stripped: String,
+    /// Number of the current line, starting at 1.
+    line_no: usize,
+    in_block_comment: bool,
+    /// Whether `next_line` returns the current line again.
+    repeat: bool,
+}
+
+impl<R: BufRead> LineReader<R> {
+    fn new(reader: R) -> Self {
+        LineReader {
+            reader,
+            line: String::new(),
+            stripped: String::new(),
+            line_no: 0,
+            in_block_comment: false,
+            repeat: false,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Option<&str>, io::Error> {
+        if self.repeat {
+            self.repeat = false;
+            return Ok(Some(self.stripped.trim()));
+        }
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        self.line_no += 1;
+        self.strip_comments();
+        Ok(Some(self.stripped.trim()))
+    }
+
+    /// Like `next_line`, but also get the line number.
+    fn next_numbered_line(
+        &mut self
+    ) -> Result<Option<(usize, &str)>, io::Error> {
+        if self.next_line()?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some((self.line_no, self.stripped.trim())))
+    }
+
+    /// Copy the current line to `stripped` without its comments, keeping
+    /// track of block comments spanning multiple lines.
+    fn strip_comments(&mut self) {
+        let (line, stripped) = (&self.line, &mut self.stripped);
+        stripped.clear();
+        let bytes = line.as_bytes();
+        let mut copy_from: usize = 0;
+        if self.in_block_comment {
+            match line.find("*/") {
+                Some(end) => copy_from = end + 2,
+                None => return
+            }
+            self.in_block_comment = false;
+        }
+        let mut in_string = false;
+        let mut i: usize = copy_from;
+        while i < bytes.len() {
+            match (bytes[i], bytes.get(i + 1)) {
+                (b'\\', _) if in_string => i += 1,
+                (b'"', _) => in_string = !in_string,
+                (b'/', Some(b'/')) if !in_string => {
+                    stripped.push_str(&line[copy_from..i]);
+                    return;
+                },
+                (b'/', Some(b'*')) if !in_string => {
+                    stripped.push_str(&line[copy_from..i]);
+                    match line[i + 2..].find("*/") {
+                        Some(n) => {
+                            i += 2 + n + 2;
+                            copy_from = i;
+                            continue;
+                        },
+                        None => {
+                            self.in_block_comment = true;
+                            return;
+                        }
+                    }
+                },
+                _ => {}
+            }
+            i += 1;
+        }
+        stripped.push_str(&line[copy_from..]);
+    }
+
+    /// Replace the lines of `block` by the next `lines_per_block()` lines,
+    /// or fewer if `is_last` is true for one of them.
+    ///
+    /// Returns whether there may be more lines to read.
+    fn read_block<F: Fn(&str) -> bool>(
+        &mut self,
+        block: &mut LineBlock,
+        is_last: F
+    ) -> Result<bool, io::Error> {
+        block.text.clear();
+        block.lines.clear();
+        let num_lines = lines_per_block();
+        while block.lines.len() < num_lines {
+            let (line_no, line) = match self.next_numbered_line()? {
+                Some(line) => line,
+                None => return Ok(false)
+            };
+            let start = block.text.len();
+            block.text.push_str(line);
+            block.lines.push((line_no, start..block.text.len()));
+            if is_last(line) { return Ok(false); }
+        }
+        Ok(true)
+    }
+
+    /// Skip the first `skip` lines, or the `FoamFile` header if `skip` is
+    /// `None` (see `lines_to_skip`).
+    fn skip_header(&mut self, skip: Option<usize>) -> Result<(), io::Error> {
+        if let Some(skip) = skip {
+            for _ in 0..skip {
+                if self.next_line()?.is_none() { break; }
+            }
+            return Ok(());
+        }
+        let mut depth: usize = 0;
+        let mut in_header = false;
+        while let Some(line) = self.next_line()? {
+            if !in_header {
+                if line.is_empty() { continue; }
+                if !line.starts_with("FoamFile") {
+                    // There is no header, so this line is data:
+                    self.repeat = true;
+                    return Ok(());
+                }
+                in_header = true;
+            }
+            for c in line.chars() {
+                if c == '{' {
+                    depth += 1;
+                } else if c == '}' {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 { return Ok(()); }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read up to the first line consisting of a number (see
+    /// `find_list_start`), i.e., the number of list entries, which is
+    /// zero if there is no such line.
+    fn list_start(&mut self) -> Result<usize, io::Error> {
+        while let Some(line) = self.next_line()? {
+            if let Ok(num) = line.parse::<usize>() {
+                return Ok(num);
+            }
+        }
+        Ok(0)
+    }
+
+    /// Get the next line that is not empty (after removing comments).
+    fn next_content_line(&mut self) -> Result<Option<&str>, io::Error> {
+        loop {
+            match self.next_line()? {
+                None => return Ok(None),
+                Some("") => {},
+                Some(_) => break
+            }
+        }
+        Ok(Some(self.stripped.trim()))
+    }
+}
+
+fn parse_internal_field_bytes<T, F>(
+    bytes: Vec<u8>,
+    path: &Path,
+    parse_fn: F,
+    all_sections: bool
+) -> Result<Vec<T>, FoamError> where
+        F: Fn(&str) -> Option<T> {
+    if is_binary_format(&bytes) {
+        return parse_internal_field_binary(&bytes, parse_fn);
+    }
+    let content = strip_comments(bytes_to_string(bytes, path)?);
+    let content: Vec<String> = resolve_includes(content, path)?
+            .split('\n')
+            .map(String::from)
+            .collect();
+    for (i, line) in content.iter().enumerate() {
+        if !line.starts_with("internalField") { continue; }
+        if line.contains("nonuniform") {
+            if let Some(list) = line.split('>').nth(1)
+                    .filter(|list| list.contains('{')) {
+                return parse_repeated_list(list, parse_fn).ok_or_else(|| {
+                    FoamError::InvalidData(format!(
+                        "Malformed internal field data line:\n{}",
+                        line
+                    ))
+                });
+            }
+            return parse_internal_field_data_nonuniform(
+                &content,
+                path,
+                i,
+                all_sections,
+                parse_fn
+            ).map(|(data, _)| data);
+        } else if line.contains("uniform") {
+            return parse_internal_field_data_uniform(
+                line,
+                parse_fn
+            );
+        }
+        return Err(FoamError::InvalidData(String::from(
+            "Malformed internal field file: Not defined as either \
+            uniform of nonuniform."
+        )));
+    }
+    Err(FoamError::InvalidData(String::from(
+        "Did not find any data in internal field file."
+    )))
+}
+
+/// Parse the internal field of a scalar field file such as `p`.
+///
+/// Uniform fields (`internalField uniform 0;`) yield a single value.
+pub fn parse_scalar_field<P: AsRef<Path>>(
+    filename: P
+) -> Result<Vec<f64>, FoamError> {
+    parse_internal_field(filename, |s| s.trim().parse::<f64>().ok())
+}
+
+/// Parse the internal field of a vector field file such as `U` into an
+/// N×3 matrix with one row per value.
+pub fn parse_vector_field_matrix<P: AsRef<Path>>(
+    filename: P
+) -> Result<DMatrix<f64>, FoamError> {
+    let data: Vec<Vector3<f64>> = parse_internal_field(
+        filename, parse_vector3)?;
+    Ok(DMatrix::from_fn(data.len(), 3, |row, col| data[row][col]))
+}
+
+/// Parse the internal field of a scalar field file such as `p` into a
+/// vector.
+pub fn parse_scalar_field_vector<P: AsRef<Path>>(
+    filename: P
+) -> Result<DVector<f64>, FoamError> {
+    Ok(DVector::from_vec(parse_scalar_field(filename)?))
+}
+
+/// Get the minimum of a scalar field, or `None` if it is empty.
+/// NaN values are ignored.
+pub fn field_min(field: &[f64]) -> Option<f64> {
+    field.iter().cloned().reduce(f64::min)
+}
+
+/// Get the maximum of a scalar field, or `None` if it is empty.
+/// NaN values are ignored.
+pub fn field_max(field: &[f64]) -> Option<f64> {
+    field.iter().cloned().reduce(f64::max)
+}
+
+/// Get the arithmetic mean of a scalar field, or `None` if it is empty.
+pub fn field_mean(field: &[f64]) -> Option<f64> {
+    if field.is_empty() { return None; }
+    Some(field.iter().sum::<f64>() / field.len() as f64)
+}
+
+/// Get the componentwise minimum of a vector field, or `None` if it is
+/// empty.
+pub fn field_min_vector(field: &[Vector3<f64>]) -> Option<Vector3<f64>> {
+    field.iter().cloned().reduce(|a, b| a.zip_map(&b, f64::min))
+}
+
+/// Get the componentwise maximum of a vector field, or `None` if it is
+/// empty.
+pub fn field_max_vector(field: &[Vector3<f64>]) -> Option<Vector3<f64>> {
+    field.iter().cloned().reduce(|a, b| a.zip_map(&b, f64::max))
+}
+
+/// Get the componentwise mean of a vector field, or `None` if it is
+/// empty.
+pub fn field_mean_vector(field: &[Vector3<f64>]) -> Option<Vector3<f64>> {
+    if field.is_empty() { return None; }
+    Some(field.iter().sum::<Vector3<f64>>() / field.len() as f64)
+}
+
+/// Get the largest magnitude of the vectors of a field, or `None` if it is
+/// empty.
+pub fn field_magnitude_max(field: &[Vector3<f64>]) -> Option<f64> {
+    field.iter().map(|v| v.norm()).reduce(f64::max)
+}
+
+/// Renumber a cell field like `FoamMesh::apply_permutation`, i.e.,
+/// value `perm[i]` becomes value `i`.
+///
+/// # Panics
+///
+/// Panics if `perm` contains an index out of bounds of `field`.
+pub fn permute_field<T: Clone>(field: &[T], perm: &[usize]) -> Vec<T> {
+    perm.iter().map(|&old_id| field[old_id].clone()).collect()
+}
+
+/// Reconstruct the internal field `field_name` at time `time` of a
+/// decomposed case without running `reconstructPar`.
+///
+/// The field of each `processorN/` directory is mapped to global cell
+/// indices using the processor's
+/// `constant/polyMesh/cellProcAddressing` file, as written by
+/// `decomposePar`.
+pub fn reconstruct_field<T, P, F>(
+    case_dir: P,
+    time: &str,
+    field_name: &str,
+    parse_fn: F
+) -> Result<Vec<T>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    let mut global: Vec<Option<T>> = Vec::new();
+    for dir in processor_dirs(case_dir)? {
+        let addressing_file = dir.join(
+            "constant/polyMesh/cellProcAddressing");
+        let addressing: Vec<usize> = FoamMesh::parse_scalars(
+            &addressing_file,
+            None
+        ).map_err(|e| match e {
+            FoamError::Io(ref io_err)
+                    if io_err.kind() == io::ErrorKind::NotFound => {
+                FoamError::InvalidData(format!(
+                    "Missing cell addressing file \"{}\", which is \
+                    required to reconstruct decomposed fields.",
+                    addressing_file.to_string_lossy()
+                ))
+            },
+            e => e
+        })?;
+        let field_file = dir.join(time).join(field_name);
+        let values: Vec<T> = parse_internal_field(&field_file, &parse_fn)?;
+        if values.len() != addressing.len() {
+            return Err(FoamError::InvalidData(format!(
+                "\"{}\" has {} values, but {} cells are addressed.",
+                field_file.to_string_lossy(),
+                values.len(),
+                addressing.len()
+            )));
+        }
+        for (cell, value) in addressing.into_iter().zip(values) {
+            if cell >= global.len() {
+                global.resize_with(cell + 1, || None);
+            }
+            if global[cell].is_some() {
+                return Err(FoamError::InvalidData(format!(
+                    "Cell {} is addressed by more than one processor.",
+                    cell
+                )));
+            }
+            global[cell] = Some(value);
+        }
+    }
+    global
+        .into_iter()
+        .enumerate()
+        .map(|(cell, value)| value.ok_or_else(|| FoamError::InvalidData(
+            format!("Cell {} is not addressed by any processor.", cell)
+        )))
+        .collect()
+}
+
+/// Parse the internal field `field_name` of every time directory of a
+/// case, returning `(time, data)` pairs sorted by time.
+///
+/// Time directories without the field are skipped.
+pub fn parse_field_timeseries<T, P, F>(
+    case_dir: P,
+    field_name: &str,
+    parse_fn: F
+) -> Result<Vec<(f64, Vec<T>)>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    let mut series: Vec<(f64, Vec<T>)> = Vec::new();
+    for (time, dir) in time_dirs(case_dir)? {
+        match parse_internal_field(dir.join(field_name), &parse_fn) {
+            Ok(data) => series.push((time, data)),
+            Err(FoamError::Io(ref e))
+                if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e)
+        }
+    }
+    Ok(series)
+}
+
+/// Parse the output of a function object such as `probes` or
+/// `residuals`, i.e., a file `postProcessing/<function>/<time>/<file>`,
+/// returning the column names and the rows of values.
+///
+/// The column names are taken from the last comment line starting with
+/// `Time`. If that line only holds `Time`, as for `probes`, the columns are
+/// named after the probe indices of a preceding `Probe` line, e.g.,
+/// `probe0`.
+/// Bracketed values are expanded into one column per component, with the
+/// suffixes `_x`, `_y`, and `_z` for vectors, e.g., `probe0_x`, and `_0`,
+/// `_1`, … otherwise.
+/// Values written as `N/A` are parsed as NaN.
+///
+/// Expects a file in the following format:
+/// ```plaintext
+/// # Probe 0 (0.05 0.05 0.005)
+/// # Probe 1 (0.02 0.08 0.005)
+/// #           Probe             0             1
+/// #            Time
+///          0.005 (0.00012 -0.00037 0) (0.0029 -0.0137 0)
+/// // …
+/// ```
+pub fn parse_postprocessing<P: AsRef<Path>>(
+    path: P
+) -> Result<(Vec<String>, Vec<Vec<f64>>), FoamError> {
+    let path = path.as_ref();
+    let content = bytes_to_string(read_bytes(path)?, path)?;
+    let mut header: Vec<String> = Vec::new();
+    let mut probes: Vec<String> = Vec::new();
+    // Number of values in each column of the first row:
+    let mut widths: Vec<usize> = Vec::new();
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            let words: Vec<&str> = comment.split_whitespace().collect();
+            match words.split_first() {
+                Some((first, _)) if first.eq_ignore_ascii_case("time") => {
+                    header = words.iter().map(|&w| String::from(w)).collect();
+                },
+                Some((&"Probe", ids)) if !ids.is_empty() && ids.iter()
+                        .all(|id| id.parse::<usize>().is_ok()) => {
+                    probes = ids.iter().map(|id| format!("probe{}", id))
+                        .collect();
+                },
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() { continue; }
+        let malformed = |reason: String| FoamError::MalformedLine {
+            file: path.to_path_buf(),
+            line: i + 1,
+            content: String::from(line),
+            reason
+        };
+        let columns = split_postprocessing_columns(line).ok_or_else(|| {
+            malformed(String::from("Could not parse the values."))
+        })?;
+        if widths.is_empty() {
+            widths = columns.iter().map(Vec::len).collect();
+        }
+        let row: Vec<f64> = columns.into_iter().flatten().collect();
+        let num_values: usize = widths.iter().sum();
+        if row.len() != num_values {
+            return Err(malformed(format!(
+                "Expected {} values like the first row, but found {}.",
+                num_values,
+                row.len()
+            )));
+        }
+        rows.push(row);
+    }
+
+    if header.len() == 1 && !probes.is_empty() {
+        header.extend(probes);
+    }
+    let names: Vec<String> = if header.len() == widths.len() {
+        header.iter().zip(&widths).flat_map(|(name, &width)| {
+            (0..width).map(move |j| match width {
+                1 => name.clone(),
+                3 => format!("{}_{}", name, ["x", "y", "z"][j]),
+                _ => format!("{}_{}", name, j)
+            })
+        }).collect()
+    } else if header.len() == widths.iter().sum::<usize>() {
+        header
+    } else {
+        return Err(FoamError::InvalidData(format!(
+            "The header of \"{}\" names {} columns, but the rows have {}.",
+            path.to_string_lossy(),
+            header.len(),
+            widths.len()
+        )));
+    };
+    Ok((names, rows))
+}
+
+/// Split a row of function object output into its columns, each holding
+/// either a single value or the components of a bracketed value.
+fn split_postprocessing_columns(line: &str) -> Option<Vec<Vec<f64>>> {
+    let parse = |word: &str| match word {
+        "N/A" => Some(f64::NAN),
+        _ => word.parse::<f64>().ok()
+    };
+    let spaced = line.replace('(', " ( ").replace(')', " ) ");
+    let mut columns: Vec<Vec<f64>> = Vec::new();
+    let mut depth: usize = 0;
+    for word in spaced.split_whitespace() {
+        match word {
+            "(" => {
+                if depth == 0 { columns.push(Vec::new()); }
+                depth += 1;
+            },
+            ")" => depth = depth.checked_sub(1)?,
+            _ if depth == 0 => columns.push(vec![parse(word)?]),
+            _ => columns.last_mut()?.push(parse(word)?)
+        }
+    }
+    if depth != 0 { return None; }
+    Some(columns)
+}
+
+/// Time series of the force coefficients written by OpenFOAM's
+/// `forceCoeffs` function object, see `parse_force_coeffs`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ForceCoeffs {
+    pub time: Vec<f64>,
+    /// Drag coefficient.
+    pub cd: Vec<f64>,
+    /// Lift coefficient.
+    pub cl: Vec<f64>,
+    /// Pitch moment coefficient.
+    pub cm: Vec<f64>,
+    /// Pressure and viscous contributions to `cd` and `cl`, if written.
+    pub cd_pressure: Option<Vec<f64>>,
+    pub cd_viscous: Option<Vec<f64>>,
+    pub cl_pressure: Option<Vec<f64>>,
+    pub cl_viscous: Option<Vec<f64>>,
+}
+
+/// Parse the output of OpenFOAM's `forceCoeffs` function object, e.g.,
+/// `postProcessing/forceCoeffs/0/coefficient.dat` (or `forceCoeffs.dat`
+/// in older versions), see `parse_postprocessing`.
+///
+/// The columns are matched by their names in the header, whose order
+/// differs between OpenFOAM versions. The pitch moment is read from
+/// `CmPitch` or `Cm`, and the pressure and viscous contributions from,
+/// e.g., `Cd(p)` and `Cd(v)`, `Cd_p` and `Cd_v`, or `Cd_pressure` and
+/// `Cd_viscous`.
+pub fn parse_force_coeffs<P: AsRef<Path>>(
+    path: P
+) -> Result<ForceCoeffs, FoamError> {
+    let (names, rows) = parse_postprocessing(&path)?;
+    let column = |aliases: &[&str]| -> Option<Vec<f64>> {
+        let i = names.iter().position(|name| aliases.contains(&&name[..]))?;
+        Some(rows.iter().map(|row| row[i]).collect())
+    };
+    let required = |aliases: &[&str]| column(aliases).ok_or_else(|| {
+        FoamError::InvalidData(format!(
+            "Missing column \"{}\" in \"{}\".",
+            aliases[0],
+            path.as_ref().to_string_lossy()
+        ))
+    });
+    Ok(ForceCoeffs {
+        time: required(&["Time", "time"])?,
+        cd: required(&["Cd"])?,
+        cl: required(&["Cl"])?,
+        cm: required(&["CmPitch", "Cm"])?,
+        cd_pressure: column(&["Cd(p)", "Cd_p", "Cd_pressure"]),
+        cd_viscous: column(&["Cd(v)", "Cd_v", "Cd_viscous"]),
+        cl_pressure: column(&["Cl(p)", "Cl_p", "Cl_pressure"]),
+        cl_viscous: column(&["Cl(v)", "Cl_v", "Cl_viscous"])
+    })
+}
+
+/// Parse the initial residuals from the log of a solver run, e.g., to
+/// chart the convergence, returning a series of `(time, residual)` pairs
+/// per field.
+///
+/// The time (or iteration of a steady-state solver) is taken from the
+/// preceding `Time = …` line. Like OpenFOAM's `residuals` function object,
+/// only the first residual of each field per time step is kept, e.g., of
+/// the first pressure corrector. Lines before the first time step are
+/// ignored.
+///
+/// Expects lines in the following format:
+/// ```plaintext
+/// Time = 0.005
+///
+/// smoothSolver:  Solving for Ux, Initial residual = 1, Final residual = …
+/// DICPCG:  Solving for p, Initial residual = 1, Final residual = …
+/// // …
+/// ```
+pub fn parse_residuals<P: AsRef<Path>>(
+    log_path: P
+) -> Result<HashMap<String, Vec<(f64, f64)>>, FoamError> {
+    lazy_static! {
+        static ref RE_RESIDUAL: Regex = Regex::new(
+            r"Solving for (\w+), Initial residual = ([^,]+),"
+        ).unwrap();
+    }
+    let path = log_path.as_ref();
+    let mut series: HashMap<String, Vec<(f64, f64)>> = HashMap::new();
+    let mut time: Option<f64> = None;
+    for (i, line) in open_buffered(path)?.lines().enumerate() {
+        let line = line?;
+        let malformed = |reason: &str| FoamError::MalformedLine {
+            file: path.to_path_buf(),
+            line: i + 1,
+            content: String::from(line.trim()),
+            reason: String::from(reason)
+        };
+        if let Some(val) = line.trim().strip_prefix("Time = ") {
+            time = Some(val.trim().parse::<f64>()
+                .map_err(|_| malformed("Could not parse the time."))?);
+            continue;
+        }
+        let (time, cap) = match (time, RE_RESIDUAL.captures(&line)) {
+            (Some(time), Some(cap)) => (time, cap),
+            _ => continue
+        };
+        let residual = cap[2].trim().parse::<f64>()
+            .map_err(|_| malformed("Could not parse the residual."))?;
+        let field_series = series.entry(String::from(&cap[1]))
+            .or_default();
+        if !matches!(field_series.last(), Some(&(last, _)) if last == time) {
+            field_series.push((time, residual));
+        }
+    }
+    Ok(series)
+}
+
+/// Write an ASCII field file with the given internal field, e.g., to
+/// provide initial conditions computed in Rust.
+///
+/// `class` is the field class such as `volScalarField` or
+/// `volVectorField`, and `write_fn` formats a single value
+/// (e.g., "(0.1 0 3.3)" for vectors).
+/// The boundary conditions of constraint patches (e.g., `empty`) are set
+/// by including OpenFOAM's `caseDicts/setConstraintTypes`, all others
+/// default to `zeroGradient`.
+pub fn write_internal_field<T, P, F>(
+    path: P,
+    data: &[T],
+    class: &str,
+    object: &str,
+    dimensions: &[i32; 7],
+    write_fn: F
+) -> Result<(), FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&T) -> String {
+    let path = path.as_ref();
+    // E.g., "volVectorField" -> "vector":
+    let value_type = class
+        .trim_start_matches("vol")
+        .trim_start_matches("surface")
+        .trim_start_matches("point")
+        .trim_end_matches("Field");
+    let mut value_type_chars = value_type.chars();
+    let value_type: String = match value_type_chars.next() {
+        Some(first) => first.to_lowercase().chain(value_type_chars).collect(),
+        None => return Err(FoamError::InvalidData(format!(
+            "Invalid field class \"{}\".",
+            class
+        )))
+    };
+    let location = path.parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|dir| dir.to_str());
+
+    let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+    write_foam_header(&mut w, class, location, object, None)?;
+    let dimensions: Vec<String> = dimensions.iter()
+        .map(|d| d.to_string())
+        .collect();
+    writeln!(w, "dimensions      [{}];\n", dimensions.join(" "))?;
+    writeln!(w, "internalField   nonuniform List<{}> ", value_type)?;
+    writeln!(w, "{}\n(", data.len())?;
+    for value in data {
+        writeln!(w, "{}", write_fn(value))?;
+    }
+    writeln!(w, ")\n;\n")?;
+    writeln!(w, "boundaryField\n{{")?;
+    writeln!(w, "    #includeEtc \"caseDicts/setConstraintTypes\"\n")?;
+    writeln!(w, "    \".*\"\n    {{")?;
+    writeln!(w, "        type            zeroGradient;")?;
+    writeln!(w, "    }}\n}}")?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Write a vector field to a CSV file with the columns
+/// `cx,cy,cz,ux,uy,uz`, i.e., one row per cell with its center and value.
+pub fn write_field_csv<P: AsRef<Path>>(
+    centers: &[Point3<f64>],
+    field: &[Vector3<f64>],
+    path: P
+) -> Result<(), FoamError> {
+    write_csv(centers, field, path, "ux,uy,uz", |w, u| {
+        write!(w, "{},{},{}", u.x, u.y, u.z)
+    })
+}
+
+/// Write a scalar field to a CSV file with the columns `cx,cy,cz,value`.
+pub fn write_scalar_csv<P: AsRef<Path>>(
+    centers: &[Point3<f64>],
+    field: &[f64],
+    path: P
+) -> Result<(), FoamError> {
+    write_csv(centers, field, path, "value", |w, v| write!(w, "{}", v))
+}
+
+fn write_csv<T, P, F>(
+    centers: &[Point3<f64>],
+    field: &[T],
+    path: P,
+    value_columns: &str,
+    write_value: F
+) -> Result<(), FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&mut io::BufWriter<std::fs::File>, &T) -> io::Result<()> {
+    if centers.len() != field.len() {
+        return Err(FoamError::CountMismatch {
+            expected: centers.len(),
+            found: field.len()
+        });
+    }
+    let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(w, "cx,cy,cz,{}", value_columns)?;
+    for (c, value) in centers.iter().zip(field) {
+        write!(w, "{},{},{},", c.x, c.y, c.z)?;
+        write_value(&mut w, value)?;
+        writeln!(w)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// Boundary condition of a single patch as given in the `boundaryField`
+/// section of a field file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchField<T> {
+    /// E.g., `fixedValue` or `zeroGradient`.
+    pub patch_type: String,
+    /// The values of the patch's `value` entry, if any.
+    /// A uniform value is returned as a single element.
+    pub value: Option<Vec<T>>,
+}
+
+/// Parse the `boundaryField` section of a field file such as `0/U`.
+///
+/// Expects a closure `parse_fn` to parse a single value to the desired
+/// type, just like `parse_internal_field`.
+///
+/// Expects a section in the following format:
+/// ```plaintext
+/// boundaryField
+/// {
+///     movingWall
+///     {
+///         type            fixedValue;
+///         value           uniform (1 0 0);
+///     }
+///     fixedWalls
+///     {
+///         type            calculated;
+///         value           nonuniform List<vector>
+/// 240
+/// (
+/// (0 0.00125 0.0025)
+/// // …
+/// )
+/// ;
+///     }
+/// }
+/// ```
+pub fn parse_boundary_field<T, P, F>(
+    filename: P,
+    parse_fn: F
+) -> Result<HashMap<String, PatchField<T>>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    let content: Vec<String> = read_field_to_string(filename.as_ref())?
+            .split('\n')
+            .map(String::from)
+            .collect();
+    let start = content.iter()
+        .position(|l| l.starts_with("boundaryField"))
+        .ok_or_else(|| FoamError::InvalidData(String::from(
+            "Did not find a boundaryField section."
+        )))?;
+
+    let mut patches: HashMap<String, PatchField<T>> = HashMap::new();
+    let mut depth: usize = 0;
+    let mut patch_name: Option<String> = None;
+    let mut patch_type = String::new();
+    let mut value: Option<Vec<T>> = None;
+    let mut i = start;
+    while i < content.len() {
+        let mut line = content[i].trim();
+        if i == start {
+            line = line.trim_start_matches("boundaryField").trim();
+        }
+        if depth < 2 && line.ends_with('{') {
+            // Beginning of the boundaryField section or of a patch,
+            // optionally preceded by the patch name.
+            let name = line.trim_end_matches('{').trim();
+            if !name.is_empty() {
+                patch_name = Some(String::from(name.trim_matches('"')));
+            }
+            depth += 1;
+        } else if depth == 1 && line == "}" {
+            break;
+        } else if depth == 1 && !line.is_empty() && !line.starts_with("//") {
+            patch_name = Some(String::from(line.trim_matches('"')));
+        } else if depth == 2 && line == "}" {
+            let name = patch_name.take().ok_or_else(|| {
+                FoamError::InvalidData(String::from(
+                    "Malformed boundaryField: Missing patch name."
+                ))
+            })?;
+            patches.insert(name, PatchField {
+                patch_type: std::mem::take(&mut patch_type),
+                value: value.take()
+            });
+            depth = 1;
+        } else if depth == 2 && line.starts_with("type") {
+            patch_type = String::from(
+                line["type".len()..].trim().trim_end_matches(';').trim()
+            );
+        } else if depth == 2 && line.starts_with("value") {
+            if line.contains("nonuniform") {
+                let repeated = line.split('>').nth(1)
+                    .and_then(|list| parse_repeated_list(list, &parse_fn));
+                if line.contains("0()") {
+                    value = Some(Vec::new());
+                } else if repeated.is_some() {
+                    value = repeated;
+                } else {
+                    let (data, last) = parse_internal_field_data_nonuniform(
+                        &content,
+                        filename.as_ref(),
+                        i,
+                        false,
+                        &parse_fn
+                    )?;
+                    // Skip to the end of the list:
+                    i = last;
+                    value = Some(data);
+                }
+            } else if line.contains("uniform") {
+                value = Some(vec![
+                    parse_uniform_value(line, &parse_fn).ok_or_else(|| {
+                        FoamError::InvalidData(format!(
+                            "Malformed uniform value: \"{}\"",
+                            line
+                        ))
+                    })?
+                ]);
+            }
+        }
+        i += 1;
+    }
+    Ok(patches)
+}
+
+/// Parse a surface scalar field file such as `phi` into one value per
+/// face of `mesh`, so that `field[face_id]` is the value of a face.
+///
+/// The values of the internal field come first, followed by the `value`
+/// entry of each patch of the `boundaryField` section in the mesh's face
+/// order. Uniform values are repeated for each face of the internal field
+/// or patch, and the faces of `empty` patches, which have no values, are
+/// zero.
+/// Fails if a patch has no values or the number of values differs from
+/// the number of faces.
+pub fn parse_surface_scalar_field<P: AsRef<Path>>(
+    filename: P,
+    mesh: &FoamMesh
+) -> Result<Vec<f64>, FoamError> {
+    let parse_fn = |s: &str| s.trim().parse::<f64>().ok();
+    let mut field: Vec<f64> = parse_internal_field(&filename, parse_fn)?;
+    if field.len() == 1 {
+        field = vec![field[0]; mesh.num_inner_faces()];
+    }
+    let mut patch_fields = parse_boundary_field(&filename, parse_fn)?;
+
+    let mut patches: Vec<(&String, &Boundary)> = mesh.boundary.iter()
+        .collect();
+    patches.sort_by_key(|(_, bd)| bd.start_face);
+    for (name, bd) in patches {
+        if field.len() != bd.start_face {
+            return Err(FoamError::CountMismatch {
+                expected: bd.start_face,
+                found: field.len()
+            });
+        }
+        let values = patch_fields.remove(name)
+            .and_then(|patch_field| patch_field.value)
+            .unwrap_or_default();
+        match values.len() {
+            0 if bd.kind() == PatchType::Empty || bd.num_faces == 0 => {
+                field.extend(vec![0.0; bd.num_faces]);
+            },
+            1 => field.extend(vec![values[0]; bd.num_faces]),
+            n if n == bd.num_faces => field.extend(values),
+            n => return Err(FoamError::InvalidData(format!(
+                "Patch \"{}\" of \"{}\" has {} values, but {} faces.",
+                name,
+                filename.as_ref().to_string_lossy(),
+                n,
+                bd.num_faces
+            )))
+        }
+    }
+    if field.len() != mesh.faces.len() {
+        return Err(FoamError::CountMismatch {
+            expected: mesh.faces.len(),
+            found: field.len()
+        });
+    }
+    Ok(field)
+}
+
+/// Where the values of a field are located on the mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldLocation {
+    Cell,
+    Face,
+    Unknown,
+}
+
+impl FieldLocation {
+    /// Get the location from a field's class (see `FoamFileHeader`),
+    /// e.g., `Cell` for `volScalarField` and `Face` for
+    /// `surfaceScalarField`.
+    pub fn from_class(class: &str) -> FieldLocation {
+        if class.starts_with("vol") {
+            FieldLocation::Cell
+        } else if class.starts_with("surface") {
+            FieldLocation::Face
+        } else {
+            FieldLocation::Unknown
+        }
+    }
+}
+
+/// The contents of a field file such as `0/U`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoamField<T> {
+    pub internal: Vec<T>,
+    pub dimensions: [i32; 7],
+    pub boundary: HashMap<String, PatchField<T>>,
+    /// E.g., `volVectorField`.
+    pub class: String,
+}
+
+/// Parse a field file such as `0/U` completely, i.e., its internal field
+/// (see `parse_internal_field`), dimensions, and boundary field (see
+/// `parse_boundary_field`).
+pub fn parse_field<T, P, F>(
+    filename: P,
+    parse_fn: F
+) -> Result<FoamField<T>, FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&str) -> Option<T> {
+    Ok(FoamField {
+        internal: parse_internal_field(&filename, &parse_fn)?,
+        dimensions: parse_field_dimensions(&filename)?,
+        boundary: parse_boundary_field(&filename, &parse_fn)?,
+        class: parse_header(&filename)?.class
+    })
+}
+
+/// Parse the single value of a line such as `value uniform (1 0 0);`.
+fn parse_uniform_value<T, F>(line: &str, parse_fn: F) -> Option<T> where
+        F: Fn(&str) -> Option<T> {
+    let val = line.split("uniform").nth(1)?.trim().strip_suffix(';')?;
+    parse_fn(val.trim())
+}
+
+/// Parse uniform data from a line.
+///
+/// Example input lines:
+/// ```plaintext
+/// value           uniform (0 0 0);
+/// value           uniform 0;
+/// ```
+///
+/// If `parse_fn` parses the whole value, e.g., `parse_vector3`, it is
+/// returned as the only element.
+/// Otherwise, the components within brackets are parsed separately and
+/// may be separated by any whitespace.
+fn parse_internal_field_data_uniform<T, F>(
+    line: &str,
+    parse_fn: F
+) -> Result<Vec<T>, FoamError> where
+        F: Fn(&str) -> Option<T> {
+    let malformed = || FoamError::InvalidData(format!(
+        "Malformed internal field uniform data line:\n{}",
+        line
+    ));
+    if let Some(val) = parse_uniform_value(line, &parse_fn) {
+        // A single value, such as a scalar or a vector.
+        return Ok(vec![val]);
+    }
+    let start = line.find('(');
+    let end = line.find(')');
+    if let (Some(start), Some(end)) = (start, end) {
+        let vals: Vec<T> = line[start+1..end]
+             .split_whitespace()
+             .filter_map(&parse_fn)
+             .collect();
+        if vals.is_empty() {
+            return Err(malformed());
+        }
+        Ok(vals)
+    } else {
+        Err(malformed())
+    }
+}
+
+/// Parse the nonuniform list following the line with index `start`,
+/// e.g., the line `internalField nonuniform List<scalar>`.
+///
+/// If `all_sections` is set, further lists directly following the
+/// closing bracket of the first one are read as well and appended.
+/// Returns the values and the index of the last line of the list, i.e.,
+/// of its closing bracket, of the last value if the bracket is glued to
+/// it, or of the `N{value}` shorthand.
+fn parse_internal_field_data_nonuniform<T, F>(
+    content: &[String],
+    path: &Path,
+    start: usize,
+    all_sections: bool,
+    parse_fn: F
+) -> Result<(Vec<T>, usize), FoamError> where
+        F: Fn(&str) -> Option<T> {
+    let mut data: Vec<T> = Vec::new();
+    let mut start = start;
+    let missing_count = || FoamError::InvalidData(String::from(
+        "Malformed internal field file: Number of expected values not \
+        given."
+    ));
+    loop {
+        let count_line = content.get(start + 1).ok_or_else(missing_count)?;
+        if let Some(repeated) = parse_repeated_list(count_line, &parse_fn) {
+            data.extend(repeated);
+            return Ok((data, start + 1));
+        }
+        let num_vals_expected = count_line.parse::<usize>()
+            .map_err(|_| missing_count())?;
+        if start + 3 + num_vals_expected > content.len() {
+            return Err(FoamError::InvalidData(String::from(
+                "Internal field file is shorter than declared."
+            )));
+        }
+        data.reserve_exact(num_vals_expected);
+        let first = start + 3;
+        for (i, line) in content[first..first+num_vals_expected]
+                .iter()
+                .enumerate() {
+            match parse_fn(strip_list_end(line).0) {
+                Some(val) => data.push(val),
+                None => return Err(FoamError::MalformedLine {
+                    file: path.to_path_buf(),
+                    line: first + i + 1,
+                    content: line.clone(),
+                    reason: String::from("Could not parse a value.")
+                })
+            }
+        }
+        // The line with the closing bracket is the new `start`, unless the
+        // bracket is glued to the last value:
+        start += 2 + num_vals_expected;
+        if !content.get(start).is_some_and(|line| strip_list_end(line).1) {
+            start += 1;
+        }
+        let has_next_section = content.len() > start + 2
+            && content[start+1].parse::<usize>().is_ok()
+            && content[start+2] == "(";
+        if !all_sections || !has_next_section {
+            return Ok((data, start));
+        }
+    }
+}
+
+/// Strip the closing bracket of a list (and a following semicolon) if it
+/// is glued to the last value, such as in `3)` or `(4 5 6));`.
+///
+/// Returns the trimmed value and whether the list was closed.
+fn strip_list_end(line: &str) -> (&str, bool) {
+    let line = line.trim();
+    let val = match line.strip_suffix(';').unwrap_or(line)
+            .trim_end()
+            .strip_suffix(')') {
+        Some(val) => val.trim_end(),
+        None => return (line, false)
+    };
+    // Otherwise, the bracket closes the value itself, e.g., a vector:
+    if val.is_empty() || val.matches('(').count() > val.matches(')').count() {
+        return (line, false);
+    }
+    (val, true)
+}
+
+/// Parse a list of words such as `2(wall movingWalls)`, optionally
+/// preceded by the type `List<word>`.
+///
+/// The number of words may be omitted, but must match if given.
+fn parse_word_list(s: &str) -> Option<Vec<String>> {
+    let s = s.trim();
+    let s = s.strip_prefix("List<word>").unwrap_or(s).trim_start();
+    let open = s.find('(')?;
+    let words: Vec<String> = s[open + 1..]
+        .strip_suffix(')')?
+        .split_whitespace()
+        .map(|word| String::from(word.trim_matches('"')))
+        .collect();
+    match s[..open].trim() {
+        "" => Some(words),
+        num if num.parse::<usize>().ok()? == words.len() => Some(words),
+        _ => None
+    }
+}
+
+/// Parse the shorthand for a list of identical values, such as `5{0}` or
+/// `5{(1 2 3)}`, expanding it to the given number of values.
+fn parse_repeated_list<T, F>(s: &str, parse_fn: F) -> Option<Vec<T>> where
+        F: Fn(&str) -> Option<T> {
+    let s = s.trim().trim_end_matches(';').trim();
+    let open = s.find('{')?;
+    let num_vals: usize = s[..open].trim().parse().ok()?;
+    let val = s[open+1..].strip_suffix('}')?.trim();
+    (0..num_vals).map(|_| parse_fn(val)).collect()
+}
+
+/// Data format of an OpenFOAM file as declared in its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoamFormat {
+    Ascii,
+    Binary,
+}
+
+/// The `FoamFile` dictionary at the beginning of each OpenFOAM file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoamFileHeader {
+    pub format: FoamFormat,
+    /// E.g., `volVectorField` or `faceList`.
+    pub class: String,
+    pub object: String,
+    pub arch: Option<String>,
+    pub location: Option<String>,
+    /// E.g., the mesh sizes in
+    /// `"nPoints:5043  nCells:3200  nFaces:11360  nInternalFaces:7840"`.
+    pub note: Option<String>,
+}
+
+/// Parse the `FoamFile` header of the given file.
+///
+/// Expects a file beginning in the following format:
+/// ```plaintext
+/// // …
+/// FoamFile
+/// {
+///     version     2.0;
+///     format      ascii;
+///     class       vectorField;
+///     location    "constant/polyMesh";
+///     object      points;
+/// }
+/// ```
+pub fn parse_header<P: AsRef<Path>>(
+    filename: P
+) -> Result<FoamFileHeader, FoamError> {
+    parse_header_bytes(&read_bytes(&filename)?)
+}
+
+fn parse_header_bytes(content: &[u8]) -> Result<FoamFileHeader, FoamError> {
+    lazy_static! {
+        static ref RE_ENTRY: Regex = Regex::new(
+            r#"(?m)^\s*(\w+)\s+("[^"]*"|[^;]*);"#
+        ).unwrap();
+    }
+    let start = find_bytes(content, b"FoamFile").ok_or_else(|| {
+        FoamError::InvalidData(String::from("Missing FoamFile header."))
+    })?;
+    // Binary data may only follow after the header, which ends with the
+    // first closing brace:
+    let end = content[start..].iter()
+        .position(|&b| b == b'}')
+        .ok_or_else(|| FoamError::MissingBracket {
+            bracket: '}',
+            context: String::from("at the end of the FoamFile header")
+        })? + start;
+    let header = String::from_utf8_lossy(&content[start..end]);
+
+    let mut entries: HashMap<&str, &str> = HashMap::new();
+    for cap in RE_ENTRY.captures_iter(&header) {
+        let (key, val) = (cap.get(1).unwrap(), cap.get(2).unwrap());
+        entries.insert(key.as_str(), val.as_str().trim().trim_matches('"'));
+    }
+    let get_required = |key: &str| -> Result<String, FoamError> {
+        entries.get(key).map(|&val| String::from(val)).ok_or_else(|| {
+            FoamError::InvalidData(format!(
+                "Missing '{}' entry in FoamFile header.",
+                key
+            ))
+        })
+    };
+    let format = match get_required("format")?.as_str() {
+        "ascii" => FoamFormat::Ascii,
+        "binary" => FoamFormat::Binary,
+        other => return Err(FoamError::InvalidData(format!(
+            "Unknown format '{}' in FoamFile header.",
+            other
+        )))
+    };
+    Ok(FoamFileHeader {
+        format,
+        class: get_required("class")?,
+        object: get_required("object")?,
+        arch: entries.get("arch").map(|&val| String::from(val)),
+        location: entries.get("location").map(|&val| String::from(val)),
+        note: entries.get("note").map(|&val| String::from(val)),
+    })
+}
+
+/// Parse the mesh sizes from the `note` in the header of a `polyMesh` file,
+/// e.g., `"nPoints:5043  nCells:3200  nFaces:11360  nInternalFaces:7840"`.
+fn parse_mesh_sizes(note: &str) -> HashMap<String, usize> {
+    note.split_whitespace()
+        .filter_map(|entry| {
+            let mut key_val = entry.splitn(2, ':');
+            let key = key_val.next()?;
+            let val = key_val.next()?.parse::<usize>().ok()?;
+            Some((String::from(key), val))
+        })
+        .collect()
+}
+
+/// Check whether the FoamFile header of a file declares `format binary;`.
+fn is_binary_format(content: &[u8]) -> bool {
+    matches!(
+        parse_header_bytes(content),
+        Ok(FoamFileHeader { format: FoamFormat::Binary, .. })
+    )
+}
+
+/// Parse the internal field of a file written with `format binary;`.
+///
+/// For a nonuniform field, expects the declared number of values and an
+/// opening bracket after the type token, followed by the raw little-endian
+/// doubles and a closing bracket:
+/// ```plaintext
+/// internalField   nonuniform List<vector>
+/// 3200
+/// (<3200 * 3 * 8 bytes>)
+/// ;
+/// ```
+/// The number of components per value is inferred from the type token.
+fn parse_internal_field_binary<T, F>(
+    content: &[u8],
+    parse_fn: F
+) -> Result<Vec<T>, FoamError> where
+        F: Fn(&str) -> Option<T> {
+    fn malformed(msg: String) -> FoamError {
+        FoamError::InvalidData(
+            format!("Malformed binary internal field file: {}", msg)
+        )
+    }
+
+    let start = match find_bytes(content, b"\ninternalField") {
+        Some(pos) => pos + 1,
+        None => return Err(FoamError::InvalidData(String::from(
+            "Did not find any data in internal field file."
+        )))
+    };
+    let line_end = content[start..].iter()
+        .position(|&b| b == b'\n')
+        .map_or(content.len(), |pos| start + pos);
+    let line = String::from_utf8_lossy(&content[start..line_end]);
+    if !line.contains("nonuniform") {
+        if line.contains("uniform") {
+            // Uniform values are written as ASCII even in binary files.
+            return parse_internal_field_data_uniform(&line, parse_fn);
+        }
+        return Err(malformed(String::from(
+            "Not defined as either uniform of nonuniform."
+        )));
+    }
+
+    let type_start = find_bytes(&content[start..], b"List<")
+        .ok_or_else(|| malformed(String::from("Missing list type.")))?
+        + start + 5;
+    let type_end = content[type_start..].iter()
+        .position(|&b| b == b'>')
+        .ok_or_else(|| malformed(String::from("Unterminated list type.")))?
+        + type_start;
+    let components: usize = match &content[type_start..type_end] {
+        b"scalar" | b"sphericalTensor" => 1,
+        b"vector" => 3,
+        b"symmTensor" => 6,
+        b"tensor" => 9,
+        other => return Err(malformed(format!(
+            "Unsupported list type \"{}\".",
+            String::from_utf8_lossy(other)
+        )))
+    };
+
+    let mut pos = type_end + 1;
+    while pos < content.len() && content[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    let count_start = pos;
+    while pos < content.len() && content[pos].is_ascii_digit() {
+        pos += 1;
+    }
+    let num_vals_expected: usize = String::from_utf8_lossy(
+            &content[count_start..pos]
+        ).parse()
+        .map_err(|_| malformed(String::from(
+            "Number of expected values not given."
+        )))?;
+    while pos < content.len() && content[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    if content.get(pos) != Some(&b'(') {
+        return Err(FoamError::MissingBracket {
+            bracket: '(',
+            context: String::from("after number of values")
+        });
+    }
+    pos += 1;
+
+    let num_bytes = num_vals_expected.checked_mul(components * 8)
+        .ok_or_else(|| malformed(format!(
+            "The declared size of {} values * {} components * 8 bytes \
+            overflows.",
+            num_vals_expected,
+            components
+        )))?;
+    let end = pos.checked_add(num_bytes)
+        .ok_or_else(|| malformed(format!(
+            "The declared size of {} bytes exceeds the addressable memory.",
+            num_bytes
+        )))?;
+    if content.len() <= end || content[end] != b')' {
+        return Err(FoamError::InvalidData(format!(
+            "Binary internal field data does not match the declared \
+            size of {} values * {} components * 8 bytes = {} bytes.",
+            num_vals_expected,
+            components,
+            num_bytes
+        )));
+    }
+
+    let vals: Vec<f64> = content[pos..end]
+        .chunks(8)
+        .map(|b| {
+            let mut buf = [0_u8; 8];
+            buf.copy_from_slice(b);
+            f64::from_le_bytes(buf)
+        })
+        .collect();
+    let mut data: Vec<T> = Vec::with_capacity(num_vals_expected);
+    for (i, val) in vals.chunks(components).enumerate() {
+        let s = if components == 1 {
+            val[0].to_string()
+        } else {
+            format!(
+                "({})",
+                val.iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            )
+        };
+        match parse_fn(&s) {
+            Some(v) => data.push(v),
+            None => return Err(FoamError::InvalidData(format!(
+                "Could not parse binary value {} (\"{}\").",
+                i,
+                s
+            )))
+        }
+    }
+    Ok(data)
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Parse a single value, using a fast float parser for `f64` and `f32`
+/// with the `fast-float` feature.
+fn parse_value<T: std::str::FromStr + 'static>(s: &str) -> Option<T> {
+    #[cfg(feature = "fast-float")]
+    {
+        use std::any::Any;
+        let mut val: Option<T> = None;
+        let any = &mut val as &mut dyn Any;
+        if let Some(val) = any.downcast_mut::<Option<f64>>() {
+            *val = fast_float2::parse(s).ok();
+        } else if let Some(val) = any.downcast_mut::<Option<f32>>() {
+            *val = fast_float2::parse(s).ok();
+        } else {
+            return s.parse::<T>().ok();
+        }
+        val
+    }
+    #[cfg(not(feature = "fast-float"))]
+    s.parse::<T>().ok()
+}
+
+fn parse_vals_from_brackets<T>(s: &str) -> Option<Vec<T>> where
+        T: std::str::FromStr + 'static {
+    s.strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_whitespace()
+        .map(parse_value::<T>)
+        .collect::<Option<Vec<T>>>()
+}
+
+/// Parse exactly `N` values given in brackets, e.g., "(1 2 3)" for
+/// `N = 3`.
+pub fn parse_components<const N: usize>(s: &str) -> Option<[f64; N]> {
+    parse_array(s)
+}
+
+fn parse_array<T, const N: usize>(s: &str) -> Option<[T; N]> where
+        T: std::str::FromStr + 'static {
+    <[T; N]>::try_from(parse_vals_from_brackets::<T>(s)?).ok()
+}
+
+pub fn parse_point3<T>(s: &str) -> Option<Point3<T>> where
+        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
+    let [x, y, z] = parse_array(s)?;
+    Some(Point3::new(x, y, z))
+}
+
+/// Parse a 2D point given as "(x y)".
+pub fn parse_point2<T>(s: &str) -> Option<Point2<T>> where
+        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
+    let [x, y] = parse_array(s)?;
+    Some(Point2::new(x, y))
+}
+
+/// Parse a 2D vector given as "(x y)".
+pub fn parse_vector2<T>(s: &str) -> Option<Vector2<T>> where
+        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
+    let [x, y] = parse_array(s)?;
+    Some(Vector2::new(x, y))
+}
+
+pub fn parse_vector3<T>(s: &str) -> Option<Vector3<T>> where
+        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
+    let [x, y, z] = parse_array(s)?;
+    Some(Vector3::new(x, y, z))
+}
+
+/// Parse a tensor given as "(xx xy xz yx yy yz zx zy zz)" (row-major).
+pub fn parse_tensor<T>(s: &str) -> Option<Matrix3<T>> where
+        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
+    let vals: [T; 9] = parse_array(s)?;
+    Some(Matrix3::new(
+        vals[0], vals[1], vals[2],
+        vals[3], vals[4], vals[5],
+        vals[6], vals[7], vals[8]
+    ))
+}
+
+/// Symmetric tensor as used for fields such as the Reynolds stress `R`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymmTensor {
+    pub xx: f64,
+    pub xy: f64,
+    pub xz: f64,
+    pub yy: f64,
+    pub yz: f64,
+    pub zz: f64,
+}
+
+/// Parse a symmetric tensor given as "(xx xy xz yy yz zz)".
+pub fn parse_symm_tensor(s: &str) -> Option<SymmTensor> {
+    let [xx, xy, xz, yy, yz, zz] = parse_components::<6>(s)?;
+    Some(SymmTensor { xx, xy, xz, yy, yz, zz })
+}
+
+/// Parse a dimension set such as "[0 2 -2 0 0 0 0]", as given by the
+/// `dimensions` entry of field files.
+///
+/// The exponents are given in the order mass, length, time, temperature,
+/// quantity, current, and luminous intensity.
+/// Text around the brackets is ignored, and the short form with only
+/// the first five exponents is accepted as well.
+pub fn parse_dimensions(s: &str) -> Option<[i32; 7]> {
+    let start = s.find('[')?;
+    let end = start + s[start..].find(']')?;
+    let vals: Vec<i32> = s[start + 1..end]
+        .split_whitespace()
+        .map(|v| v.parse::<i32>().ok())
+        .collect::<Option<Vec<i32>>>()?;
+    if vals.len() != 5 && vals.len() != 7 { return None; }
+    let mut dimensions = [0; 7];
+    dimensions[..vals.len()].copy_from_slice(&vals);
+    Some(dimensions)
+}
+
+/// A scalar with a name and a dimension set, e.g., a transport property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionedScalar {
+    pub name: String,
+    /// See `parse_dimensions`.
+    pub dimensions: [i32; 7],
+    pub value: f64,
+}
+
+/// Parse a dimensioned scalar entry such as "nu [0 2 -1 0 0 0 0] 1e-05;".
+///
+/// The old format repeating the name ("nu nu [0 2 -1 0 0 0 0] 1e-05;")
+/// is supported as well.
+pub fn parse_dimensioned_scalar(s: &str) -> Option<DimensionedScalar> {
+    let start = s.find('[')?;
+    let end = start + s[start..].find(']')?;
+    let name = s[..start].split_whitespace().last()?;
+    let value = s[end + 1..]
+        .trim()
+        .trim_end_matches(';')
+        .trim()
+        .parse::<f64>()
+        .ok()?;
+    Some(DimensionedScalar {
+        name: String::from(name),
+        dimensions: parse_dimensions(&s[start..=end])?,
+        value,
+    })
+}
+
+/// Parse the dimension set given by the `dimensions` entry of a field
+/// file.
+pub fn parse_field_dimensions<P: AsRef<Path>>(
+    filename: P
+) -> Result<[i32; 7], FoamError> {
+    let content = read_field_to_string(filename.as_ref())?;
+    let line = content
+        .split('\n')
+        .find(|line| line.starts_with("dimensions"))
+        .ok_or_else(|| FoamError::InvalidData(format!(
+            "No dimensions found in \"{}\".",
+            filename.as_ref().to_string_lossy()
+        )))?;
+    parse_dimensions(line).ok_or_else(|| FoamError::InvalidData(format!(
+        "Malformed dimensions in \"{}\": \"{}\"",
+        filename.as_ref().to_string_lossy(),
+        line
+    )))
+}
+
+/// Parse a zones file such as `cellZones`.
+///
+/// Expects a file in the following format:
+/// ```plaintext
+/// // …
+///
+/// 2
+/// (
+/// porosity
+/// {
+///     type cellZone;
+/// cellLabels      List<label> 3(0 1 2);
+/// }
+/// heater
+/// {
+///     type cellZone;
+/// cellLabels      List<label>
+/// 120
+/// (
+/// 42
+/// // …
+/// )
+/// ;
+/// }
+/// )
+/// ```
+///
+/// Returns the entries of each zone, mapping each key to either the items
+/// of its list (e.g., the cell labels) or to its plain value tokens.
+/// Returns an empty map if the file does not exist.
+fn parse_zones<P: AsRef<Path>>(
+    filename: P
+) -> Result<HashMap<String, HashMap<String, Vec<String>>>, FoamError> {
+    let content = match read_to_string(&filename) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(HashMap::new());
+        },
+        Err(e) => return Err(e.into())
+    };
+    let skip = lines_to_skip(&content, None);
+    let data: Vec<&str> = content.split('\n')
+        .skip(skip)
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .collect();
+    let data = data.join("\n");
+    let mut tokens = tokenize(&data).into_iter();
+    let missing_bracket = |bracket: char| FoamError::MissingBracket {
+        bracket,
+        context: format!(
+            "in zones file \"{}\"",
+            filename.as_ref().to_string_lossy()
+        )
+    };
+
+    // Skip the number of zones:
+    if tokens.find(|&t| t == "(").is_none() {
+        return Ok(HashMap::new());
+    }
+    let mut zones: HashMap<String, HashMap<String, Vec<String>>> =
+        HashMap::new();
+    loop {
+        let name = tokens.next().ok_or_else(|| missing_bracket(')'))?;
+        if name == ")" { break; }
+        if tokens.next() != Some("{") {
+            return Err(missing_bracket('{'));
+        }
+        let mut entries: HashMap<String, Vec<String>> = HashMap::new();
+        loop {
+            let key = tokens.next().ok_or_else(|| missing_bracket('}'))?;
+            if key == "}" { break; }
+            let mut depth: usize = 0;
+            let mut is_list = false;
+            let mut vals: Vec<String> = Vec::new();
+            let mut list_items: Vec<String> = Vec::new();
+            loop {
+                match tokens.next() {
+                    None => return Err(missing_bracket('}')),
+                    Some(";") if depth == 0 => break,
+                    Some("(") => {
+                        depth += 1;
+                        is_list = true;
+                    },
+                    Some(")") => depth = depth.saturating_sub(1),
+                    Some(t) if depth > 0 => list_items.push(String::from(t)),
+                    Some(t) => vals.push(String::from(t)),
+                }
+            }
+            entries.insert(
+                String::from(key),
+                if is_list { list_items } else { vals }
+            );
+        }
+        zones.insert(String::from(name), entries);
+    }
+    Ok(zones)
+}
+
+/// Get the labels of a zone entry such as `cellLabels` as indices.
+fn parse_zone_labels(
+    zone_name: &str,
+    entries: &HashMap<String, Vec<String>>,
+    key: &str
+) -> Result<Vec<usize>, FoamError> {
+    entries.get(key)
+        .ok_or_else(|| FoamError::InvalidData(format!(
+            "Zone \"{}\" is missing its {} entry.",
+            zone_name,
+            key
+        )))?
+        .iter()
+        .map(|label| label.parse::<usize>().map_err(|_| {
+            FoamError::InvalidData(format!(
+                "Malformed label \"{}\" in zone \"{}\".",
+                label,
+                zone_name
+            ))
+        }))
+        .collect()
+}
+
+/// Parse a set file such as a `cellSet` written by `topoSet`.
+///
+/// Expects a file in the following format:
+/// ```plaintext
+/// // …
+///
+/// 80
+/// (
+/// 1560
+/// 1561
+/// // …
+/// )
+/// ```
+/// Short sets may also be written on a single line, e.g., `3(0 1 2)`.
+/// The file may be gzip-compressed.
+pub fn parse_set<P: AsRef<Path>>(path: P) -> Result<Vec<usize>, FoamError> {
+    FoamMesh::parse_scalars(path, None)
+}
+
+/// Split text into brackets, braces, semicolons, and the words between them.
+fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens: Vec<&str> = Vec::new();
+    let mut word_start: Option<usize> = None;
+    for (i, c) in s.char_indices() {
+        let is_delimiter = matches!(c, '(' | ')' | '{' | '}' | ';');
+        if c.is_whitespace() || is_delimiter {
+            if let Some(start) = word_start.take() {
+                tokens.push(&s[start..i]);
+            }
+            if is_delimiter {
+                tokens.push(&s[i..i + 1]);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
+/// A value of an OpenFOAM dictionary, see `parse_dict`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FoamDict {
+    Scalar(f64),
+    /// A word or quoted string (without the quotes), e.g., `PCG` or `$p`.
+    Word(String),
+    /// A list in parentheses or brackets, or an entry consisting of
+    /// several values such as `nu [0 2 -1 0 0 0 0] 1e-05;`.
+    List(Vec<FoamDict>),
+    Dict(HashMap<String, FoamDict>),
+}
+
+impl FoamDict {
+    /// Look up an entry by its dot-separated path of keys, e.g.,
+    /// `solvers.p.tolerance`.
+    pub fn get(&self, path: &str) -> Option<&FoamDict> {
+        path.split('.').try_fold(self, |dict, key| match dict {
+            FoamDict::Dict(entries) => entries.get(key),
+            _ => None
+        })
+    }
+
+    pub fn as_scalar(&self) -> Option<f64> {
+        match self {
+            FoamDict::Scalar(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    pub fn as_word(&self) -> Option<&str> {
+        match self {
+            FoamDict::Word(word) => Some(word),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[FoamDict]> {
+        match self {
+            FoamDict::List(list) => Some(list),
+            _ => None
+        }
+    }
+}
+
+/// Parse a dictionary file such as `transportProperties` or
+/// `fvSolution` into a `FoamDict::Dict`.
+///
+/// The `FoamFile` header is not included.
+/// Files included by `#include "file"` are spliced in (see
+/// `parse_internal_field`), other directives such as `#inputMode` are
+/// skipped. Variables such as `$p` are not expanded.
+/// The size prefix of lists such as `2(inlet outlet)` is dropped.
+pub fn parse_dict<P: AsRef<Path>>(path: P) -> Result<FoamDict, FoamError> {
+    let path = path.as_ref();
+    let content = read_field_to_string(path)?;
+    let tokens = tokenize_dict(&content);
+    let mut tokens = tokens.iter().peekable();
+    let mut entries = parse_dict_tree(&content, &mut tokens, None)?;
+    entries.remove("FoamFile");
+    Ok(FoamDict::Dict(entries))
+}
+
+/// The time control settings of a case's `system/controlDict`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlDict {
+    /// `None` if the case does not declare a `startTime`, e.g., when
+    /// starting from the latest time.
+    pub start_time: Option<f64>,
+    pub end_time: f64,
+    pub delta_t: f64,
+    /// E.g., `timeStep` or `adjustableRunTime`. Defaults to `timeStep`.
+    pub write_control: String,
+    /// In time steps or simulated time, depending on `write_control`.
+    pub write_interval: f64,
+}
+
+/// Parse the time control settings from the `system/controlDict` of the
+/// given case directory.
+///
+/// Other entries, including sub-dictionaries such as `functions`, are
+/// ignored.
+pub fn parse_control_dict<P: AsRef<Path>>(
+    case_dir: P
+) -> Result<ControlDict, FoamError> {
+    let path = case_dir.as_ref().join("system").join("controlDict");
+    // Close the top-level dictionary for `parse_dict_entries`:
+    let content = read_field_to_string(&path)? + "\n}";
+    let tokens = tokenize_dict(&content);
+    let mut tokens = tokens.iter();
+    let entries = parse_dict_entries(&content, &mut tokens, "controlDict")?;
+    if tokens.next().is_some() {
+        return Err(FoamError::InvalidData(String::from(
+            "Unexpected '}' in \"controlDict\"."
+        )));
+    }
+    let get_val = |key: &str| -> Result<Option<f64>, FoamError> {
+        entries.get(key).map(|val| val.parse::<f64>().map_err(|_| {
+            FoamError::InvalidData(format!(
+                "Malformatted controlDict entry: \"{} {};\"", key, val))
+        })).transpose()
+    };
+    let missing = |key: &str| FoamError::InvalidData(format!(
+        "Missing '{}' entry in controlDict.", key));
+    Ok(ControlDict {
+        start_time: get_val("startTime")?,
+        end_time: get_val("endTime")?.ok_or_else(|| missing("endTime"))?,
+        delta_t: get_val("deltaT")?.ok_or_else(|| missing("deltaT"))?,
+        write_interval: get_val("writeInterval")?
+            .ok_or_else(|| missing("writeInterval"))?,
+        write_control: entries.get("writeControl")
+            .map_or_else(|| String::from("timeStep"), |&val| val.to_string()),
+    })
+}
+
+/// List the times of the time directories (e.g., `0`, `0.5`, or `1e-3`)
+/// in the given case directory, sorted in ascending order.
+pub fn time_directories<P: AsRef<Path>>(
+    case_dir: P
+) -> Result<Vec<f64>, FoamError> {
+    Ok(time_dirs(case_dir)?.into_iter().map(|(time, _)| time).collect())
+}
+
+/// Get the latest time of the given case directory, or `None` if there
+/// are no time directories.
+pub fn latest_time<P: AsRef<Path>>(
+    case_dir: P
+) -> Result<Option<f64>, FoamError> {
+    Ok(time_directories(case_dir)?.last().cloned())
+}
+
+/// Get the times and paths of all time directories of a case, sorted by
+/// time.
+fn time_dirs<P: AsRef<Path>>(
+    case_dir: P
+) -> Result<Vec<(f64, PathBuf)>, FoamError> {
+    let mut dirs: Vec<(f64, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(&case_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() { continue; }
+        let name = entry.file_name();
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => continue
+        };
+        if name == "constant" || name == "system"
+                || name.starts_with("processor") {
+            continue;
+        }
+        if let Ok(time) = name.parse::<f64>() {
+            if time.is_finite() {
+                dirs.push((time, entry.path()));
+            }
+        }
+    }
+    dirs.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+    Ok(dirs)
+}
+
+/// Write the `FoamFile` header of an ASCII file.
+fn write_foam_header<W: Write>(
+    w: &mut W,
+    class: &str,
+    location: Option<&str>,
+    object: &str,
+    note: Option<&str>
+) -> io::Result<()> {
+    writeln!(w, "FoamFile\n{{")?;
+    writeln!(w, "    version     2.0;")?;
+    writeln!(w, "    format      ascii;")?;
+    writeln!(w, "    class       {};", class)?;
+    if let Some(note) = note {
+        writeln!(w, "    note        \"{}\";", note)?;
+    }
+    if let Some(location) = location {
+        writeln!(w, "    location    \"{}\";", location)?;
+    }
+    writeln!(w, "    object      {};", object)?;
+    writeln!(w, "}}\n")?;
+    Ok(())
+}
+
+/// Write an ASCII file with a header and a list of values, each
+/// formatted by `write_value` on its own line.
+fn write_foam_list<T, P, F>(
+    path: P,
+    class: &str,
+    location: &str,
+    object: &str,
+    note: Option<&str>,
+    values: &[T],
+    write_value: F
+) -> Result<(), FoamError> where
+        P: AsRef<Path>,
+        F: Fn(&mut io::BufWriter<std::fs::File>, &T) -> io::Result<()> {
+    let mut w = io::BufWriter::new(std::fs::File::create(path)?);
+    write_foam_header(&mut w, class, Some(location), object, note)?;
+    writeln!(w, "{}\n(", values.len())?;
+    for value in values {
+        write_value(&mut w, value)?;
+        writeln!(w)?;
+    }
+    writeln!(w, ")")?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Get the `constant/<region>/polyMesh/` directory of a case.
+fn poly_mesh_dir<P: AsRef<Path>>(case_dir: P, region: &str) -> PathBuf {
+    case_dir.as_ref().join("constant").join(region).join("polyMesh")
+}
+
+/// Get the `processorN/` directories of a decomposed case, sorted by `N`.
+fn processor_dirs<P: AsRef<Path>>(
+    case_dir: P
+) -> Result<Vec<PathBuf>, FoamError> {
+    let mut dirs: Vec<(usize, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(&case_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() { continue; }
+        let name = entry.file_name();
+        let number = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("processor"))
+            .and_then(|n| n.parse::<usize>().ok());
+        if let Some(number) = number {
+            dirs.push((number, entry.path()));
+        }
+    }
+    if dirs.is_empty() {
+        return Err(FoamError::InvalidData(format!(
+            "No processor directories found in \"{}\".",
+            case_dir.as_ref().to_string_lossy()
+        )));
+    }
+    dirs.sort_by_key(|(number, _)| *number);
+    Ok(dirs.into_iter().map(|(_, dir)| dir).collect())
+}
+
+/// Find the line stating the number of list elements, starting at line
+/// `skip`.
+///
+/// Returns the number of elements and the index of the line following
+/// it (or zero and the number of lines if there is none).
+fn find_list_start(lines: &[&str], skip: usize) -> (usize, usize) {
+    for (i, line) in lines.iter().enumerate().skip(skip) {
+        if let Ok(num) = line.trim().parse::<usize>() {
+            return (num, i + 1);
+        }
+    }
+    (0, lines.len())
+}
+
+/// Number of lines handed to each thread by `parse_lines`.
+const LINES_PER_CHUNK: usize = 1 << 14;
+
+/// Parse each of the given lines using `parse_line`, preserving their
+/// order.
+///
+/// `parse_line` is called with the index and content of a line and
+/// returns `None` for lines without data.
+/// With the `parallel` feature, chunks of lines are parsed concurrently.
+fn parse_lines<T, F>(
+    lines: &[&str],
+    parse_line: F
+) -> Result<Vec<T>, FoamError> where
+        T: Send,
+        F: Fn(usize, &str) -> Result<Option<T>, FoamError> + Sync {
+    let parse_chunk = |(chunk_id, chunk): (usize, &[&str])| {
+        let mut data: Vec<T> = Vec::with_capacity(chunk.len());
+        for (i, line) in chunk.iter().enumerate() {
+            let line_id = chunk_id * LINES_PER_CHUNK + i;
+            if let Some(val) = parse_line(line_id, line)? {
+                data.push(val);
+            }
+        }
+        Ok(data)
+    };
+    #[cfg(feature = "parallel")]
+    let chunks: Result<Vec<Vec<T>>, FoamError> = lines
+        .par_chunks(LINES_PER_CHUNK)
+        .enumerate()
+        .map(parse_chunk)
+        .collect();
+    #[cfg(not(feature = "parallel"))]
+    let chunks: Result<Vec<Vec<T>>, FoamError> = lines
+        .chunks(LINES_PER_CHUNK)
+        .enumerate()
+        .map(parse_chunk)
+        .collect();
+    Ok(chunks?.into_iter().flatten().collect())
+}
+
+/// Lines read by `LineReader::read_block` to be parsed by `parse_block`.
+#[derive(Default)]
+struct LineBlock {
+    text: String,
+    /// Number and range in `text` of each line without comments.
+    lines: Vec<(usize, std::ops::Range<usize>)>,
+}
+
+/// Number of lines read by `LineReader::read_block`, i.e., a chunk of
+/// `LINES_PER_CHUNK` lines for each thread.
+fn lines_per_block() -> usize {
+    #[cfg(feature = "parallel")]
+    let num_threads = rayon::current_num_threads();
+    #[cfg(not(feature = "parallel"))]
+    let num_threads: usize = 1;
+    LINES_PER_CHUNK * num_threads
+}
+
+/// Parse each chunk of `LINES_PER_CHUNK` lines of `block` using
+/// `parse_chunk`, preserving their order.
+///
+/// With the `parallel` feature, the chunks are parsed concurrently.
+fn parse_block<T, F>(
+    block: &LineBlock,
+    parse_chunk: F
+) -> Result<Vec<T>, FoamError> where
+        T: Send,
+        F: Fn(&[(usize, std::ops::Range<usize>)]) -> Result<T, FoamError>
+            + Sync {
+    #[cfg(feature = "parallel")]
+    return block.lines.par_chunks(LINES_PER_CHUNK).map(&parse_chunk).collect();
+    #[cfg(not(feature = "parallel"))]
+    block.lines.chunks(LINES_PER_CHUNK).map(parse_chunk).collect()
+}
+
+/// Get the number of lines to skip before the actual data of a file.
+///
+/// If `skip` is `None`, this is the number of lines up to and including
+/// the closing brace of the `FoamFile` header (or zero if there is none).
+fn lines_to_skip(content: &str, skip: Option<usize>) -> usize {
+    if let Some(skip) = skip { return skip; }
+    let mut in_header = false;
+    let mut depth: usize = 0;
+    for (i, line) in content.split('\n').enumerate() {
+        if !in_header {
+            in_header = line.trim_start().starts_with("FoamFile");
+            if !in_header { continue; }
+        }
+        for c in line.chars() {
+            if c == '{' {
+                depth += 1;
+            } else if c == '}' {
+                depth = depth.saturating_sub(1);
+                if depth == 0 { return i + 1; }
+            }
+        }
+    }
+    0
+}
+
+/// Read a text file (see `read_bytes`) with comments removed (see
+/// `strip_comments`).
+fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String, io::Error> {
+    Ok(strip_comments(bytes_to_string(read_bytes(&path)?, &path)?))
+}
+
+/// Read a field or dictionary file like `read_to_string`, with its
+/// `#include` directives resolved (see `resolve_includes`).
+fn read_field_to_string(path: &Path) -> Result<String, FoamError> {
+    resolve_includes(read_to_string(path)?, path)
+}
+
+/// Replace each `#include "file"` or `#includeIfPresent "file"` line of
+/// `content`, which was read from `path`, with the contents of the
+/// referenced file, recursively.
+///
+/// Relative paths are resolved against the directory of the including
+/// file first, and then against the `system` and `constant` directories
+/// next to it (i.e., of the case if a time directory's file includes
+/// them).
+/// Other directives such as `#includeEtc` are kept as they are.
+fn resolve_includes(content: String, path: &Path) -> Result<String, FoamError> {
+    if !content.contains("#include") {
+        return Ok(content);
+    }
+    resolve_includes_recursive(content, path, &mut HashSet::new())
+}
+
+fn resolve_includes_recursive(
+    content: String,
+    path: &Path,
+    visited: &mut HashSet<PathBuf>
+) -> Result<String, FoamError> {
+    let canonical = |p: &Path| {
+        std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf())
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut candidate_dirs: Vec<PathBuf> = vec![dir.to_path_buf()];
+    if let Some(case_dir) = dir.parent() {
+        candidate_dirs.push(case_dir.join("system"));
+        candidate_dirs.push(case_dir.join("constant"));
+    }
+    visited.insert(canonical(path));
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.split('\n') {
+        let (name, optional) = match include_target(line) {
+            Some(target) => target,
+            None => {
+                lines.push(String::from(line));
+                continue;
+            }
+        };
+        let file = candidate_dirs.iter()
+            .map(|dir| dir.join(name))
+            .find(|file| file.exists());
+        let file = match file {
+            Some(file) => file,
+            None if optional => continue,
+            None => return Err(FoamError::InvalidData(format!(
+                "Could not find \"{}\" included by \"{}\".",
+                name,
+                path.to_string_lossy()
+            )))
+        };
+        if visited.contains(&canonical(&file)) {
+            return Err(FoamError::InvalidData(format!(
+                "Cyclic include of \"{}\" in \"{}\".",
+                file.to_string_lossy(),
+                path.to_string_lossy()
+            )));
+        }
+        lines.push(resolve_includes_recursive(
+            read_to_string(&file)?, &file, visited)?);
+    }
+
+    visited.remove(&canonical(path));
+    Ok(lines.join("\n"))
+}
+
+/// Get the file name of an `#include "file"` (optional: `false`) or
+/// `#includeIfPresent "file"` (optional: `true`) line.
+fn include_target(line: &str) -> Option<(&str, bool)> {
+    let line = line.trim();
+    let (rest, optional) = match line.strip_prefix("#includeIfPresent") {
+        Some(rest) => (rest, true),
+        None => (line.strip_prefix("#include")?, false)
+    };
+    let name = rest.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name, optional))
+}
+
+/// Remove `// …` line comments and `/* … */` block comments, except
+/// within quoted strings.
+///
+/// Newlines within block comments are kept, such that line numbers
+/// remain valid.
+fn strip_comments(content: String) -> String {
+    if !content.contains("//") && !content.contains("/*") {
+        return content;
+    }
+    let bytes = content.as_bytes();
+    let mut stripped = String::with_capacity(content.len());
+    let mut copy_from: usize = 0;
+    let mut in_string = false;
+    let mut i: usize = 0;
+    while i < bytes.len() {
+        match (bytes[i], bytes.get(i + 1)) {
+            (b'\\', _) if in_string => i += 1,
+            (b'"', _) => in_string = !in_string,
+            (b'\n', _) => in_string = false,
+            (b'/', Some(b'/')) if !in_string => {
+                stripped.push_str(
+                    content[copy_from..i].trim_end_matches(&[' ', '\t'][..]));
+                i = content[i..].find('\n').map_or(bytes.len(), |n| i + n);
+                copy_from = i;
+                continue;
+            },
+            (b'/', Some(b'*')) if !in_string => {
+                stripped.push_str(&content[copy_from..i]);
+                let end = content[i + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |n| i + 2 + n + 2);
+                for _ in content[i..end].matches('\n') {
+                    stripped.push('\n');
+                }
+                i = end;
+                copy_from = i;
+                continue;
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    stripped.push_str(&content[copy_from..]);
+    stripped
+}
+
+/// Read the contents of a file, transparently decompressing it if it
+/// is gzip-compressed.
+///
+/// If `path` does not exist, but `path` with an additional `.gz` suffix
+/// does, the latter is read instead.
+/// Compressed files are recognized by the gzip magic bytes
+/// (`0x1f 0x8b`), regardless of their file extension.
+fn read_bytes<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, io::Error> {
+    let path = existing_path(path.as_ref());
+    let read_error = |e| read_error(&path, e);
+    decompress(std::fs::read(&path).map_err(read_error)?)
+        .map_err(read_error)
+}
+
+/// Open a file for buffered reading like `read_bytes` reads it, i.e.,
+/// transparently decompressing it if it is gzip-compressed.
+fn open_buffered(path: &Path) -> Result<Box<dyn BufRead>, io::Error> {
+    let path = existing_path(path);
+    buffered(std::fs::File::open(&path).map_err(|e| read_error(&path, e))?)
+}
+
+/// Buffer `reader`, transparently decompressing it if it is
+/// gzip-compressed (see `read_bytes`).
+fn buffered<'a, R: Read + 'a>(
+    reader: R
+) -> Result<Box<dyn BufRead + 'a>, io::Error> {
+    let mut reader = io::BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        return Ok(Box::new(io::BufReader::new(GzDecoder::new(reader))));
+    }
+    Ok(Box::new(reader))
+}
+
+/// Memory-map a file for reading like `open_buffered` reads it.
+#[cfg(feature = "mmap")]
+fn open_mapped(path: &Path) -> Result<Box<dyn BufRead>, io::Error> {
+    let path = existing_path(path);
+    let read_error = |e| read_error(&path, e);
+    let file = std::fs::File::open(&path).map_err(read_error)?;
+    // Safety: Modifying the file while it is mapped is undefined behavior,
+    // which `FoamMesh::new_mmap` documents.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(read_error)?;
+    if map.starts_with(&[0x1f, 0x8b]) {
+        return Ok(Box::new(io::BufReader::new(
+            GzDecoder::new(io::Cursor::new(map)))));
+    }
+    Ok(Box::new(io::Cursor::new(map)))
+}
+
+/// Get `path`, or `path` with an additional `.gz` suffix if only the
+/// latter exists.
+fn existing_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        let mut gz_path = path.to_path_buf().into_os_string();
+        gz_path.push(".gz");
+        let gz_path = PathBuf::from(gz_path);
+        if gz_path.exists() {
+            return gz_path;
+        }
+    }
+    path.to_path_buf()
+}
+
+fn read_error(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(
+        e.kind(),
+        format!(
+            "Could not read \"{}\": {}",
+            path.to_string_lossy(),
+            e
+        )
+    )
+}
+
+/// Name used in place of a file name in errors for data read with one of
+/// the `_reader` functions.
+const READER_PATH: &str = "<reader>";
+
+/// Reason of a `FoamError::MalformedLine` for an empty line inside a data
+/// block in strict mode (see `ParseOptions`).
+const EMPTY_LINE: &str = "Empty line inside a data block.";
+
+/// Reason of a `FoamError::MalformedLine` for a line without data inside
+/// a data block in strict mode (see `ParseOptions`).
+const NO_DATA: &str = "Line without data inside a data block.";
+
+/// Smallest magnitude treated as non-zero, like OpenFOAM's `VSMALL`.
+const VSMALL: f64 = 1e-300;
+
+/// Check whether a cell volume is large enough to divide by.
+///
+/// Like in OpenFOAM, only volumes below `VSMALL` count as zero. A cutoff
+/// such as `f64::EPSILON` would reject the cells of micro-scale meshes.
+fn is_nonzero_volume(volume: f64) -> bool {
+    volume.abs() > VSMALL
+}
+
+/// Read all data from `reader` as text, like `read_to_string` does for
+/// files, referring to it as `path` in errors.
+fn read_reader_to_string<R: Read, P: AsRef<Path>>(
+    mut reader: R,
+    path: P
+) -> Result<String, io::Error> {
+    let mut bytes: Vec<u8> = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(strip_comments(bytes_to_string(decompress(bytes)?, path)?))
+}
+
+/// Decompress the given data if it starts with the gzip magic bytes
+/// (`0x1f 0x8b`), and return it unchanged otherwise.
+fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, io::Error> {
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return Ok(bytes);
+    }
+    let mut decompressed: Vec<u8> = Vec::new();
+    GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Convert the contents of a text file to a string, normalizing Windows
+/// (`\r\n`) line endings to `\n`.
+fn bytes_to_string<P: AsRef<Path>>(
+    bytes: Vec<u8>,
+    path: P
+) -> Result<String, io::Error> {
+    match String::from_utf8(bytes) {
+        Err(e) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Could not read \"{}\" as text: {}",
+                path.as_ref().to_string_lossy(),
+                e
+            )
+        )),
+        Ok(s) if s.contains('\r') => Ok(s.replace("\r\n", "\n")),
+        Ok(s) => Ok(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_boundary() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/constant/polyMesh/boundary");
+        let boundaries: HashMap<String, Boundary> = FoamMesh::parse_boundary(
+            d,
+            Some(10) // default skip…
+        ).unwrap();
+        let bd_fixed_wall = boundaries.get("fixedWalls").unwrap();
+        assert_eq!(bd_fixed_wall.boundary_type, "wall");
+        assert_eq!(bd_fixed_wall.num_faces, 240);
+        assert_eq!(bd_fixed_wall.start_face, 7920);
+    }
+
+    #[test]
+    fn test_patch_types() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/boundary_types/boundary");
+        let boundaries = FoamMesh::parse_boundary(d, None).unwrap();
+        assert_eq!(boundaries.len(), 5);
+        assert_eq!(boundaries["walls"].kind(), PatchType::Wall);
+        assert_eq!(boundaries["frontAndBack"].kind(), PatchType::Empty);
+        assert_eq!(boundaries["left"].kind(), PatchType::Cyclic);
+        assert!(boundaries["left"].kind().is_coupled());
+        assert!(!boundaries["walls"].kind().is_coupled());
+        assert_eq!(boundaries["left"].neighbour_patch.as_deref(),
+            Some("right"));
+        assert_eq!(boundaries["right"].neighbour_patch.as_deref(),
+            Some("left"));
+        assert_eq!(boundaries["walls"].neighbour_patch, None);
+        assert_eq!(boundaries["coupled"].kind(),
+            PatchType::Other(String::from("mappedWall")));
+        assert_eq!(boundaries["coupled"].start_face, 1640);
+        assert_eq!(PatchType::from("symmetryPlane"), PatchType::SymmetryPlane);
+    }
+
+    #[test]
+    fn test_parse_boundary_groups() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/boundary_groups/boundary");
+        let boundaries = FoamMesh::parse_boundary(d, None).unwrap();
+        assert_eq!(boundaries["inlet"].in_groups, vec!["inletOutlet"]);
+        assert_eq!(boundaries["outlet"].in_groups, vec!["inletOutlet"]);
+        assert_eq!(boundaries["heater"].in_groups, vec!["wall", "heated"]);
+        assert!(boundaries["frontAndBack"].in_groups.is_empty());
+        assert_eq!(boundaries["heater"].start_face, 800);
+        assert_eq!(parse_word_list("(a b)").unwrap(), vec!["a", "b"]);
+        assert!(parse_word_list("3(a b)").is_none());
+    }
+
+    #[test]
+    fn test_parse_boundary_formats() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let reference = FoamMesh::parse_boundary(
+            d.join("resources/test/cavity/constant/polyMesh/boundary"),
+            None
+        ).unwrap();
+        for name in &["compact", "expanded"] {
+            let boundaries = FoamMesh::parse_boundary(
+                d.join("resources/test/boundary_formats").join(name),
+                None
+            ).unwrap();
+            assert_eq!(boundaries, reference, "{}", name);
+        }
+        // All patches on a single line, with braces glued to the names:
+        let boundaries = FoamMesh::parse_boundary_reader(
+            &b"3(movingWall{type wall;inGroups 1(wall);nFaces 80;\
+                startFace 7840;}fixedWalls{type wall;inGroups 1(wall);\
+                nFaces 240;startFace 7920;}frontAndBack{type empty;\
+                inGroups 1(empty);nFaces 3200;startFace 8160;})"[..],
+            Some(0)
+        ).unwrap();
+        assert_eq!(boundaries, reference);
+        let res = FoamMesh::parse_boundary_reader(
+            &b"1(inlet {type patch; nFaces 2;})"[..], Some(0));
+        match res {
+            Err(FoamError::InvalidData(msg)) => {
+                assert!(msg.contains("'startFace'"), "{}", msg)
+            },
+            _ => panic!("Expected a missing entry")
+        }
+        let res = FoamMesh::parse_boundary_reader(
+            &b"1(inlet {type patch; nFaces 2; startFace 0;"[..], Some(0));
+        assert!(matches!(
+            res,
+            Err(FoamError::MissingBracket { bracket: '}', .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let header = parse_header(
+            d.join("resources/test/cavity/constant/polyMesh/points")
+        ).unwrap();
+        assert_eq!(header, FoamFileHeader {
+            format: FoamFormat::Ascii,
+            class: String::from("vectorField"),
+            object: String::from("points"),
+            arch: None,
+            location: Some(String::from("constant/polyMesh")),
+            note: None,
+        });
+        let header = parse_header(
+            d.join("resources/test/binary/U")
+        ).unwrap();
+        assert_eq!(header.format, FoamFormat::Binary);
+        assert_eq!(header.class, "volVectorField");
+        assert_eq!(header.arch.unwrap(), "LSB;label=32;scalar=64");
+    }
+
+    #[test]
+    fn test_parse_points() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/constant/polyMesh/points");
+        let points: Vec<Point3<f64>> = FoamMesh::parse_points(
+            d,
+            Some(10) // default skip…
+        ).unwrap();
+        assert_relative_eq!(points[0], Point3::new(0_f64, 0_f64, 0_f64));
+        assert_relative_eq!(
+            points[5042],
+            Point3::new(0.1_f64, 0.1_f64, 0.01_f64)
+        );
+    }
+
+    #[test]
+    fn test_parse_faces() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/constant/polyMesh/faces");
+        let faces: Vec<Vec<usize>> = FoamMesh::parse_faces(
+            d,
+            Some(10) // default skip…
+        ).unwrap();
+        assert_eq!(faces[0], vec![1, 42, 1723, 1682]);
+    }
+
+    #[test]
+    fn test_parse_faces_flat() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test");
+        for file in &["cavity/constant/polyMesh/faces", "wrapped_faces/faces",
+                "comments/faces"] {
+            let faces = FoamMesh::parse_faces(d.join(file), None).unwrap();
+            let flat = FoamMesh::parse_faces_flat(d.join(file), None).unwrap();
+            assert_eq!(flat.len(), faces.len());
+            for (i, face) in faces.iter().enumerate() {
+                let expected: Vec<u32> =
+                    face.iter().map(|&p| p as u32).collect();
+                assert_eq!(flat.face_vertices(i), &expected[..]);
+            }
+            assert_eq!(FlatFaces::try_from(&faces[..]).unwrap(), flat);
+        }
+        let flat = FoamMesh::parse_faces_flat(
+            d.join("cavity/constant/polyMesh/faces"), None).unwrap();
+        assert_eq!(flat.face_vertices(0), &[1, 42, 1723, 1682]);
+        assert_eq!(flat.iter().count(), 11360);
+        let res = FoamMesh::parse_faces_flat(d.join("garbage/owner"), None);
+        assert!(matches!(
+            res,
+            Err(FoamError::MalformedLine { line: 23, .. })
+        ));
+        assert!(FlatFaces::try_from(&[vec![1usize << 40]][..]).is_err());
+        // Compressed files are streamed like in `parse_faces`:
+        let gz = FoamMesh::parse_faces_flat(
+            d.join("cavity_gz/constant/polyMesh/faces"), None).unwrap();
+        assert_eq!(gz, flat);
+    }
+
+    #[test]
+    fn test_flat_faces() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let m = FoamMesh::new(&d).unwrap();
+        let flat = m.flat_faces().unwrap();
+        assert_eq!(flat.len(), m.num_faces());
+        assert_eq!(flat.offsets.len(), m.num_faces() + 1);
+        for (face_id, face) in m.faces.iter().enumerate() {
+            let expected: Vec<u32> = face.iter()
+                .map(|&p| idx(p) as u32)
+                .collect();
+            assert_eq!(flat.face_vertices(face_id), &expected[..]);
+        }
+        assert!(flat.iter().eq(m.faces.iter().map(|face| face.iter()
+            .map(|&p| idx(p) as u32)
+            .collect::<Vec<u32>>())));
+        assert_eq!(flat, FoamMesh::parse_faces_flat(
+            d.join("constant/polyMesh/faces"), None).unwrap());
+
+        let m = FoamMeshBuilder::new(&d)
+            .with_connectivity(false)
+            .build()
+            .unwrap();
+        assert!(m.flat_faces().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_line_reader() {
+        let content = "FoamFile\n{\n    location \"a//b\";\n}\n\
+            /* block\ncomment */ 2 // number\n(\n1 /* one */\n2\n)\n";
+        let mut lines = LineReader::new(content.as_bytes());
+        lines.skip_header(None).unwrap();
+        assert_eq!(lines.line_no, 4);
+        assert_eq!(lines.next_line().unwrap(), Some(""));
+        assert_eq!(lines.next_line().unwrap(), Some("2"));
+        assert_eq!(lines.next_numbered_line().unwrap(), Some((7, "(")));
+        assert_eq!(lines.next_line().unwrap(), Some("1"));
+        let data: Vec<u8> =
+            FoamMesh::parse_scalars_reader(content.as_bytes(), None).unwrap();
+        assert_eq!(data, vec![1, 2]);
+
+        // Without a header, no lines are skipped:
+        let mut lines = LineReader::new("\n3\n".as_bytes());
+        lines.skip_header(None).unwrap();
+        assert_eq!(lines.next_content_line().unwrap(), Some("3"));
+        assert_eq!(lines.next_line().unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_wrapped_faces() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let faces = FoamMesh::parse_faces(
+            d.join("resources/test/wrapped_faces/faces"),
+            None
+        ).unwrap();
+        assert_eq!(faces, vec![
+            vec![0, 1, 2, 3],
+            vec![0, 1, 2, 3, 4, 5, 6, 7],
+            (0..12).collect::<Vec<usize>>(),
+            vec![4, 5, 6],
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multiple_blocks() {
+        // More lines than fit into a block, with faces spanning the
+        // boundaries of blocks and chunks:
+        let num = lines_per_block() + LINES_PER_CHUNK / 2;
+        let mut faces = format!("{}\n(\n", num);
+        let mut scalars = format!("{}\n(\n", num);
+        for i in 0..num {
+            let sep = if i % LINES_PER_CHUNK == LINES_PER_CHUNK - 3 {
+                '\n'
+            } else {
+                ' '
+            };
+            faces.push_str(&format!("3({}{}{} {})\n", i, sep, i + 1, i + 2));
+            scalars.push_str(&format!("{}\n", i));
+        }
+        faces.push_str(")\n");
+        scalars.push_str(")\n");
+        let parsed = FoamMesh::parse_faces_reader(faces.as_bytes(), None)
+            .unwrap();
+        assert_eq!(parsed.len(), num);
+        assert!(parsed.iter().enumerate()
+            .all(|(i, face)| *face == vec![i, i + 1, i + 2]));
+        let parsed: Vec<usize> =
+            FoamMesh::parse_scalars_reader(scalars.as_bytes(), None)
+                .unwrap();
+        assert_eq!(parsed, (0..num).collect::<Vec<usize>>());
+
+        // Errors report the line number within the whole file:
+        let scalars = scalars.replacen(&format!("\n{}\n", num - 1), "\nx\n", 1);
+        let res: Result<Vec<usize>, FoamError> =
+            FoamMesh::parse_scalars_reader(scalars.as_bytes(), None);
+        assert!(matches!(res, Err(FoamError::MalformedLine { line, .. })
+            if line == num + 2));
+    }
+
+    #[test]
+    fn test_scan_face_line() {
+        let mut vals = Vec::new();
+        assert_eq!(scan_face_line("4(1 42 1723 1682)", &mut vals), Some(0));
+        assert_eq!(vals, vec![4, 1, 42, 1723, 1682]);
+        vals.clear();
+        assert_eq!(scan_face_line("  3(7 8", &mut vals), Some(1));
+        assert_eq!(scan_face_line("9)", &mut vals), Some(-1));
+        assert_eq!(vals, vec![3, 7, 8, 9]);
+        assert_eq!(scan_face_line("99999999999999999999999", &mut vals),
+            None);
+        let faces = FoamMesh::parse_faces_reader(
+            "2\n(\n3(0 1 2)\n4(3 4 5)\n)\n".as_bytes(), Some(0));
+        assert!(matches!(faces, Err(FoamError::MalformedLine { line: 4, .. })));
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip_comments(String::from(
+                "a // b\nc /* d\ne */ f\n\"g // h\" /* i */\n"
+            )),
+            "a\nc \n f\n\"g // h\" \n"
+        );
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/comments");
+        let faces = FoamMesh::parse_faces(d.join("faces"), None).unwrap();
+        assert_eq!(faces, vec![vec![0, 1, 2, 3], vec![4, 5, 6]]);
+        let points = FoamMesh::parse_points(d.join("points"), None).unwrap();
+        assert_eq!(points, vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+        ]);
+        assert_eq!(
+            parse_header(d.join("points")).unwrap().location,
+            Some(String::from("constant//polyMesh"))
+        );
+    }
+
+    #[test]
+    fn test_parse_scalars() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let owners: Vec<usize> = FoamMesh::parse_scalars(
+            d.join("resources/test/cavity/constant/polyMesh/owner"),
+            Some(10) // default skip…
+        ).unwrap();
+        assert_eq!(owners[0], 0);
+        assert_eq!(owners[11359], 3199);
+    }
+
+    #[test]
+    fn test_parse_crlf() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let lf = FoamMesh::parse_boundary(
+            d.join("resources/test/cavity/constant/polyMesh/boundary"), None
+        ).unwrap();
+        let crlf = FoamMesh::parse_boundary(
+            d.join("resources/test/crlf/boundary"), None
+        ).unwrap();
+        assert_eq!(crlf, lf);
+        let data: &[u8] = b"3\r\n(\r\n(0 0 0)\r\n(1 0 0)\r\n(0 1 0)\r\n)\r\n";
+        let points = FoamMesh::parse_points_reader(data, Some(0)).unwrap();
+        assert_eq!(points[2], Point3::new(0.0, 1.0, 0.0));
+        let owners: Vec<usize> = FoamMesh::parse_scalars_reader(
+            &b"2\r\n(\r\n4\r\n2\r\n)\r\n"[..], Some(0)).unwrap();
+        assert_eq!(owners, vec![4, 2]);
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let data: &[u8] =
+            b"FoamFile\n{\n    object owner;\n}\n\n3\n(\n0\n1\n1\n)\n";
+        let owners: Vec<usize> = FoamMesh::parse_scalars_reader(
+            io::Cursor::new(data), None).unwrap();
+        assert_eq!(owners, vec![0, 1, 1]);
+        let faces = FoamMesh::parse_faces_reader(
+            &b"2\n(\n3(0 1 2)\n3(1 2)\n)\n"[..], Some(0));
+        match faces {
+            Err(FoamError::MalformedLine { file, line, .. }) => {
+                assert_eq!(file, PathBuf::from("<reader>"));
+                assert_eq!(line, 4);
+            },
+            _ => panic!("Expected a malformed line")
+        }
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity_gz/constant/polyMesh/points.gz");
+        let points = FoamMesh::parse_points_reader(
+            std::fs::File::open(d).unwrap(), None).unwrap();
+        assert_eq!(points.len(), 5043);
+        let u: Vec<Vector3<f64>> = parse_internal_field_reader(
+            &b"internalField nonuniform List<vector> 2{(1 2 3)};"[..],
+            parse_vector3
+        ).unwrap();
+        assert_eq!(u, vec![Vector3::new(1.0, 2.0, 3.0); 2]);
+    }
+
+    #[test]
+    fn test_parse_long_header() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let filename = d.join("resources/test/long_header/owner");
+        let content = read_to_string(&filename).unwrap();
+        assert_eq!(lines_to_skip(&content, None), 12);
+        assert_eq!(lines_to_skip(&content, Some(10)), 10);
+        let owners: Vec<usize> = FoamMesh::parse_scalars(
+            &filename,
+            None
+        ).unwrap();
+        assert_eq!(owners, vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_parse_error_kinds() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        // Points can't be parsed as scalars:
+        let res: Result<Vec<f64>, FoamError> = FoamMesh::parse_scalars(
+            d.join("resources/test/cavity/constant/polyMesh/points"),
+            None
+        );
+        match res {
+            Err(FoamError::MalformedLine { line, content, .. }) => {
+                assert_eq!(line, 21);
+                assert_eq!(content, "(0 0 0)");
+            },
+            _ => panic!("Expected a malformed line")
+        }
+        let res: Result<Vec<f64>, FoamError> = FoamMesh::parse_scalars_reader(
+            &b"3\n(\n1\n\n2\n)\n"[..], Some(0));
+        match res {
+            Err(FoamError::CountMismatch { expected, found }) => {
+                assert_eq!(expected, 3);
+                assert_eq!(found, 2);
+            },
+            _ => panic!("Expected a count mismatch")
+        }
+        let res: Result<Vec<usize>, FoamError> = FoamMesh::parse_scalars(
+            d.join("resources/test/garbage/owner"),
+            None
+        );
+        match res {
+            Err(e @ FoamError::MalformedLine { .. }) => {
+                let msg = e.to_string();
+                assert!(msg.contains("line 23 of"), "{}", msg);
+                assert!(msg.contains("(\"0.x1\")"), "{}", msg);
+            },
+            _ => panic!("Expected a malformed line")
+        }
+        let res = FoamMesh::parse_points(d.join("does/not/exist"), None);
+        assert!(matches!(res, Err(FoamError::Io(_))));
+    }
+
+    #[test]
+    fn test_count_accessors() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.num_faces(), 11360);
+        assert_eq!(m.num_inner_faces(), 7840);
+        assert_eq!(m.num_boundary_faces(), 80 + 240 + 3200);
+        assert_eq!(m.num_points(), 5043);
+        assert_eq!(m.num_cells(), 3200);
+        assert_eq!(m.to_string(),
+            "3200 cells, 11360 faces (7840 internal), 5043 points");
+    }
+
+    #[test]
+    fn test_summary() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.bounding_box(), Some((
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.1, 0.1, 0.01)
+        )));
+        let summary = m.summary();
+        assert!(summary.contains("3200 cells"), "{}", summary);
+        assert!(summary.contains("(0, 0, 0) to (0.1, 0.1, 0.01)"));
+        assert!(summary.contains("3 patches:"));
+        for name in &["movingWall (wall)", "fixedWalls (wall)",
+                "frontAndBack (empty): 3200 faces"] {
+            assert!(summary.contains(name), "{}", summary);
+        }
+    }
+
+    #[test]
+    fn test_parse_postprocessing() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity/postProcessing");
+        let (names, rows) = parse_postprocessing(
+            d.join("probes/0/U")).unwrap();
+        assert_eq!(names, vec!["Time", "probe0_x", "probe0_y", "probe0_z",
+            "probe1_x", "probe1_y", "probe1_z"]);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], vec![0.005, 0.000123, -0.000374, 0.0,
+            0.00293, -0.0137, 0.0]);
+        assert_eq!(rows[3][0], 0.02);
+
+        let (names, rows) = parse_postprocessing(
+            d.join("probes/0/p")).unwrap();
+        assert_eq!(names, vec!["Time", "probe0", "probe1"]);
+        assert_eq!(rows[1], vec![0.01, -0.0236, 1.2e-05]);
+
+        let (names, rows) = parse_postprocessing(
+            d.join("residuals/0/residuals.dat")).unwrap();
+        assert_eq!(names, vec!["Time", "p", "Ux", "Uy"]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2][..3], [3.0, 0.0712, 0.0045]);
+        // Fields that were not solved for are NaN:
+        assert!(rows[2][3].is_nan());
+    }
+
+    #[test]
+    fn test_parse_force_coeffs() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/force_coeffs");
+        // Layout of recent versions, without the pressure/viscous split:
+        let coeffs = parse_force_coeffs(d.join("coefficient.dat")).unwrap();
+        assert_eq!(coeffs.time, vec![1.0, 2.0, 3.0]);
+        assert_eq!(coeffs.cd.last(), Some(&0.3871));
+        assert_eq!(coeffs.cl, vec![0.0051, 0.0043, 0.0046]);
+        assert_eq!(coeffs.cm[2], -0.0369);
+        assert_eq!(coeffs.cd_pressure, None);
+        assert_eq!(coeffs.cl_viscous, None);
+
+        // Older layout, with `Cm` first and the pressure/viscous split:
+        let old = parse_force_coeffs(d.join("forceCoeffs.dat")).unwrap();
+        assert_eq!(old.cd.last(), Some(&0.3871));
+        assert_eq!(old.cl, coeffs.cl);
+        assert_eq!(old.cm, coeffs.cm);
+        assert_eq!(old.cd_pressure.unwrap()[2], 0.34839);
+        assert_eq!(old.cd_viscous.unwrap()[2], 0.03871);
+        assert_eq!(old.cl_viscous.unwrap()[0], 0.000255);
+
+        // A file of another function object lacks the coefficients:
+        assert!(parse_force_coeffs(d.join(
+            "../cavity/postProcessing/residuals/0/residuals.dat")).is_err());
+    }
+
+    #[test]
+    fn test_parse_residuals() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let residuals = parse_residuals(
+            d.join("resources/test/cavity/log.icoFoam")).unwrap();
+        let mut fields: Vec<&String> = residuals.keys().collect();
+        fields.sort();
+        assert_eq!(fields, vec!["Ux", "Uy", "p"]);
+        assert_eq!(residuals["Ux"], vec![
+            (0.005, 1.0),
+            (0.01, 0.160686),
+            (0.015, 0.0728316),
+        ]);
+        // Only the first pressure corrector of each time step is kept:
+        assert_eq!(residuals["p"].len(), 3);
+        assert_eq!(residuals["p"][1], (0.01, 0.428925));
+    }
+
+    #[test]
+    fn test_empty_mesh() {
+        // The owner file declares zero faces:
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/empty_mesh");
+        let m = FoamMeshBuilder::new(&d).validate(true).build().unwrap();
+        assert_eq!(m.num_cells(), 0);
+        assert_eq!(m.num_faces(), 0);
+        assert!(m.cell_faces.is_empty() && m.boundary.is_empty());
+    }
+
+    #[test]
+    fn test_mesh_builder() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test");
+        // Only the boundary and the points:
+        let m = FoamMeshBuilder::new(d.join("cavity"))
+            .with_connectivity(false)
+            .build()
+            .unwrap();
+        assert_eq!(m.boundary.len(), 3);
+        assert_eq!(m.boundary["movingWall"].num_faces, 80);
+        assert_eq!(m.num_points(), 5043);
+        assert!(m.faces.is_empty() && m.owners.is_empty());
+        assert!(m.cell_faces.is_empty());
+        // The sizes declared by the owner file:
+        assert_eq!(m.num_cells(), 3200);
+        assert_eq!(m.num_inner_faces(), 7840);
+
+        // Only the boundary:
+        let m = FoamMeshBuilder::new(d.join("cavity"))
+            .with_points(false)
+            .with_connectivity(false)
+            .build()
+            .unwrap();
+        assert_eq!(m.boundary.len(), 3);
+        assert!(m.points.is_empty());
+        assert!(FoamMeshBuilder::new(d.join("cavity"))
+            .with_points(false)
+            .validate(true)
+            .build()
+            .is_err());
+
+        // Everything, like `FoamMesh::new`, plus cell centers:
+        let m = FoamMeshBuilder::new(d.join("cavity"))
+            .compute_cell_centers(true)
+            .validate(true)
+            .build()
+            .unwrap();
+        assert_eq!(m.faces, FoamMesh::new(d.join("cavity")).unwrap().faces);
+        assert_relative_eq!(
+            m.cell_centers.unwrap()[3199],
+            Point3::new(0.09875, 0.09875, 0.0075),
+            epsilon = 1e-12
+        );
+        let m = FoamMeshBuilder::new(d.join("multi_region"))
+            .region("solid")
+            .build()
+            .unwrap();
+        assert_eq!(m.region, "solid");
+
+        // A moving mesh with points written to the time directory:
+        let m = FoamMeshBuilder::new(d.join("moving_mesh")).build().unwrap();
+        assert_relative_eq!(m.cell_volumes()[0], 1.0, epsilon = 1e-12);
+        let m = FoamMeshBuilder::new(d.join("moving_mesh"))
+            .time("0.5")
+            .build()
+            .unwrap();
+        assert_relative_eq!(m.cell_volumes()[0], 8.0, epsilon = 1e-12);
+        assert_eq!(m.faces.len(), 6);
+    }
+
+    #[test]
+    fn test_ensure_connectivity() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let full = FoamMesh::new(&d).unwrap();
+        let mut m = FoamMeshBuilder::new(&d)
+            .with_connectivity(false)
+            .build()
+            .unwrap();
+        // A bounding box does not need the connectivity:
+        assert_eq!(m.bounding_box(), full.bounding_box());
+        assert_eq!(m.num_cells(), 3200);
+        assert_eq!(m.cell_neighbor_cells(0), None);
+        assert!(!m.is_cell_on_boundary(0, None));
+        // The neighbors are loaded on demand:
+        assert_eq!(m.load_cell_neighbor_cells(0).unwrap(),
+            full.cell_neighbor_cells(0));
+        assert!(m.is_cell_on_boundary(0, None));
+        assert_eq!(m.load_cell_neighbor_cells(3200).unwrap(), None);
+        // Probing falls back to the nearest cell without the connectivity:
+        let mut centers_only = FoamMeshBuilder::new(&d)
+            .with_connectivity(false)
+            .build()
+            .unwrap();
+        centers_only.read_cell_centers(d.join("0.5/C")).unwrap();
+        let x: Vec<f64> = (0..3200).map(|cell_id| cell_id as f64).collect();
+        assert_eq!(centers_only.probe_scalar(
+            &x, &Point3::new(0.00125, 0.00125, 0.0025)), Some(0.0));
+
+        m.ensure_connectivity().unwrap();
+        assert_eq!(m.cell_neighbor_cells(0), full.cell_neighbor_cells(0));
+        assert_eq!(m.cell_neighbor_cells(1234),
+            full.cell_neighbor_cells(1234));
+        assert_eq!(m.cell_faces, full.cell_faces);
+        assert_eq!(m.neighbors, full.neighbors);
+        assert_eq!(m.num_cells(), 3200);
+        m.validate().unwrap();
+        // Loading it again does nothing:
+        m.ensure_connectivity().unwrap();
+        assert_eq!(m.faces, full.faces);
+    }
+
+    #[test]
+    fn test_divergence() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mut m = FoamMesh::new(&d).unwrap();
+        m.compute_cell_centers();
+        let centers = m.cell_centers.clone().unwrap();
+        let wall_cells: HashSet<usize> = m.boundary_cells("movingWall")
+            .into_iter()
+            .chain(m.boundary_cells("fixedWalls"))
+            .collect();
+        let interior = (0..m.num_cells())
+            .filter(|cell| !wall_cells.contains(cell));
+
+        // A solid body rotation around the z axis is divergence-free:
+        let u: Vec<Vector3<f64>> = centers.iter()
+            .map(|c| Vector3::new(-c.y, c.x, 0.0))
+            .collect();
+        let div = m.divergence(&u);
+        assert_eq!(div.len(), 3200);
+        for cell in interior.clone() {
+            assert_relative_eq!(div[cell], 0.0, epsilon = 1e-9);
+        }
+
+        let u: Vec<Vector3<f64>> = centers.iter()
+            .map(|c| Vector3::new(c.x, 2.0 * c.y, 0.0))
+            .collect();
+        let div = m.divergence(&u);
+        for cell in interior {
+            assert_relative_eq!(div[cell], 3.0, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_curl() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mut m = FoamMesh::new(&d).unwrap();
+        m.compute_cell_centers();
+        let centers = m.cell_centers.clone().unwrap();
+        let wall_cells: HashSet<usize> = m.boundary_cells("movingWall")
+            .into_iter()
+            .chain(m.boundary_cells("fixedWalls"))
+            .collect();
+
+        // A solid body rotation around the z axis with a rotation rate of
+        // 3 rad/s has a vorticity of 6 1/s:
+        let omega = 3.0;
+        let u: Vec<Vector3<f64>> = centers.iter()
+            .map(|c| omega * Vector3::new(-c.y, c.x, 0.0))
+            .collect();
+        let curl = m.curl(&u);
+        assert_eq!(curl.len(), 3200);
+        for cell in (0..m.num_cells()).filter(|c| !wall_cells.contains(c)) {
+            assert_relative_eq!(curl[cell].x, 0.0, epsilon = 1e-9);
+            assert_relative_eq!(curl[cell].y, 0.0, epsilon = 1e-9);
+            assert_relative_eq!(curl[cell].z, 2.0 * omega, epsilon = 1e-9);
+            assert_relative_eq!(curl[cell].norm(), 2.0 * omega,
+                epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_new_mesh() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.num_cells(), 3200);
+        assert_eq!(m.cell_faces.len(), m.num_cells());
+        if let Err(e) = m.read_cell_centers(m.path.join("0.5/C")) {
+            panic!("{:?}", e);
+        }
+        assert_relative_eq!(
+            m.cell_centers.unwrap()[3199],
+            Point3::new(0.09875_f64, 0.09875_f64, 0.0075_f64)
+        );
+    }
+
+    #[test]
+    fn test_parse_internal_field_binary() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let flow: Vec<Vector3<f64>> = parse_internal_field(
+            d.join("resources/test/binary/U"),
+            parse_vector3
+        ).unwrap();
+        assert_eq!(flow.len(), 4);
+        assert_relative_eq!(
+            flow[0],
+            Vector3::new(6.014e-05, -6.06836e-05, -4.94881e-07)
+        );
+        assert_relative_eq!(flow[2], Vector3::new(1.0, -0.5, 0.25));
+    }
+
+    #[test]
+    fn test_parse_internal_field_binary_truncated() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let err = parse_internal_field(
+            d.join("resources/test/binary/U_truncated"),
+            parse_vector3::<f64>
+        ).unwrap_err();
+        assert!(matches!(err, FoamError::InvalidData(_)));
+        assert!(err.to_string().contains("96 bytes"));
+    }
+
+    #[test]
+    fn test_parse_internal_field_binary_oversized() {
+        // Counts whose size in bytes, or end offset, overflows:
+        for num in &[usize::MAX, usize::MAX / 24] {
+            let content = format!(
+                "FoamFile\n{{\n    format binary;\n}}\n\
+                internalField   nonuniform List<vector>\n{}\n()\n;\n",
+                num
+            );
+            let res = parse_internal_field_binary(
+                content.as_bytes(), parse_vector3::<f64>);
+            assert!(matches!(res, Err(FoamError::InvalidData(_))));
+        }
+    }
+
+    #[test]
+    fn test_parse_gzipped() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let points: Vec<Point3<f64>> = FoamMesh::parse_points(
+            d.join("resources/test/cavity_gz/constant/polyMesh/points.gz"),
+            None
+        ).unwrap();
+        assert_eq!(points.len(), 5043);
+        // A missing file is substituted by its .gz counterpart:
+        let owners: Vec<usize> = FoamMesh::parse_scalars(
+            d.join("resources/test/cavity_gz/constant/polyMesh/owner"),
+            None
+        ).unwrap();
+        assert_eq!(owners[11359], 3199);
+        // Compressed files are detected regardless of their name:
+        let p: Vec<f64> = parse_internal_field(
+            d.join("resources/test/cavity_gz/0.5/p"),
+            |s| s.parse::<f64>().ok()
+        ).unwrap();
+        assert_eq!(p.len(), 3200);
+    }
+
+    #[test]
+    fn test_new_mesh_gzipped() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity_gz/")).unwrap();
+        let m_ref = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.points, m_ref.points);
+        assert_eq!(m.faces, m_ref.faces);
+        assert_eq!(m.neighbors, m_ref.neighbors);
+        assert_eq!(m.boundary.len(), 3);
+    }
+
+    #[test]
+    fn test_cell_adjacency_csr() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let (row_offsets, col_indices) = mesh.cell_adjacency_csr();
+        assert_eq!(row_offsets.len(), 3201);
+        assert_eq!(col_indices.len(), 2 * mesh.num_inner_faces());
+        assert_eq!(row_offsets[3200], col_indices.len());
+        // The corner cell 0 is adjacent to cells 1, 40, and (in the second
+        // layer) 1600:
+        let mut neighbors = col_indices[row_offsets[0]..row_offsets[1]]
+            .to_vec();
+        neighbors.sort_unstable();
+        assert_eq!(neighbors, vec![1, 40, 1600]);
+    }
+
+    #[test]
+    fn test_parse_dict() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("resources/test/dict/nestedDict");
+        let dict = parse_dict(&path).unwrap();
+        assert!(dict.get("FoamFile").is_none());
+        assert_eq!(dict.get("simulationType"),
+            Some(&FoamDict::Word(String::from("RAS"))));
+        assert_eq!(dict.get("RAS.turbulence").and_then(FoamDict::as_word),
+            Some("on"));
+        assert_eq!(dict.get("RAS.kEpsilonCoeffs.Cmu")
+            .and_then(FoamDict::as_scalar), Some(0.09));
+        assert!(dict.get("RAS.kEpsilonCoeffs.Cmu.x").is_none());
+        assert!(dict.get("RAS.missing").is_none());
+        let nu = dict.get("nu").and_then(FoamDict::as_list).unwrap();
+        assert_eq!(nu.len(), 2);
+        assert_eq!(nu[0].as_list().unwrap().len(), 7);
+        assert_eq!(nu[1].as_scalar(), Some(1e-05));
+        assert_eq!(dict.get("patches"), Some(&FoamDict::List(vec![
+            FoamDict::Word(String::from("inlet")),
+            FoamDict::Word(String::from("outlet"))
+        ])));
+        assert_eq!(dict.get("(U|k)")
+            .and_then(|d| d.get("relTol"))
+            .and_then(FoamDict::as_scalar), Some(0.1));
+        assert_eq!(dict.get("title").and_then(FoamDict::as_word),
+            Some("lid-driven cavity; coarse"));
+
+        path.pop();
+        path.pop();
+        path.push("cavity/system/fvSolution");
+        let dict = parse_dict(&path).unwrap();
+        assert_eq!(dict.get("solvers.p.solver").and_then(FoamDict::as_word),
+            Some("PCG"));
+        assert_eq!(dict.get("PISO.nCorrectors").and_then(FoamDict::as_scalar),
+            Some(2.0));
+        assert_eq!(dict.get("solvers.pFinal.$p"),
+            Some(&FoamDict::List(vec![])));
+    }
+
+    #[test]
+    fn test_parse_control_dict() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("resources/test/cavity");
+        let control = parse_control_dict(&path).unwrap();
+        assert_eq!(control, ControlDict {
+            start_time: Some(0.0),
+            end_time: 0.5,
+            delta_t: 0.005,
+            write_control: String::from("timeStep"),
+            write_interval: 1.0,
+        });
+
+        path.pop();
+        path.push("control_dict");
+        let control = parse_control_dict(&path).unwrap();
+        assert_relative_eq!(control.delta_t, 2.5e-4);
+        assert_eq!(control.start_time, Some(0.1));
+        assert_relative_eq!(control.end_time, 2.0);
+        assert_eq!(control.write_control, "adjustableRunTime");
+        assert_relative_eq!(control.write_interval, 0.05);
+
+        path.pop();
+        path.push("missing");
+        assert!(matches!(parse_control_dict(&path), Err(FoamError::Io(_))));
+    }
+
+    #[test]
+    fn test_time_directories() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        assert_eq!(time_directories(&d).unwrap(), vec![0.0, 0.5]);
+        let mesh = FoamMesh::new(&d).unwrap();
+        assert_eq!(mesh.latest_time().unwrap(), Some(0.5));
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/decomposed");
+        assert_eq!(latest_time(&d).unwrap(), None);
+    }
+
+    #[test]
+    fn test_cell_points() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        // Points per layer: 41 * 41 = 1681
+        assert_eq!(
+            mesh.cell_points(0),
+            vec![0, 1, 41, 42, 1681, 1682, 1722, 1723]
+        );
+        assert!(mesh.cell_points(3200).is_empty());
+        let connectivity = mesh.cell_point_connectivity();
+        assert_eq!(connectivity.len(), 3200);
+        assert!(connectivity.iter().all(|points| points.len() == 8));
+    }
+
+    #[test]
+    fn test_point_cells() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let point_cells = mesh.point_cells();
+        assert_eq!(point_cells.len(), 5043);
+        assert_eq!(point_cells[0], vec![0]);
+        // Interior point in the middle layer:
+        assert_eq!(
+            point_cells[1681 + 42],
+            vec![0, 1, 40, 41, 1600, 1601, 1640, 1641]
+        );
+    }
+
+    #[test]
+    fn test_face_addressing() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        assert_eq!(mesh.face_owner(0), Some(0));
+        assert_eq!(mesh.face_neighbor(0), Some(1));
+        assert!(mesh.face_is_internal(0));
+        assert!(mesh.face_is_internal(7839));
+        assert!(!mesh.face_is_internal(7840));
+        assert_eq!(mesh.face_owner(11359), Some(3199));
+        assert_eq!(
+            mesh.face_neighbor(11359),
+            Some(mesh.boundary["frontAndBack"].boundary_id)
+        );
+        assert_eq!(mesh.face_owner(11360), None);
+        assert_eq!(mesh.face_neighbor(11360), None);
+        assert!(!mesh.face_is_internal(11360));
+    }
+
+    #[test]
+    fn test_new_mesh_declared_sizes() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let sizes = parse_mesh_sizes(
+            "nPoints:5043  nCells:3200  nFaces:11360  nInternalFaces:7840");
+        assert_eq!(sizes["nCells"], 3200);
+        assert_eq!(sizes["nInternalFaces"], 7840);
+        match FoamMesh::new(d.join("resources/test/truncated")) {
+            Err(FoamError::InvalidData(msg)) => {
+                assert!(msg.contains("nPoints:16, but has 15"), "{}", msg)
+            },
+            _ => panic!("Expected a size mismatch")
+        }
+    }
+
+    #[test]
+    fn test_boundary_lookup() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        assert_eq!(mesh.boundary_name_of_face(7840).unwrap(), "movingWall");
+        assert_eq!(mesh.boundary_name_of_face(7919).unwrap(), "movingWall");
+        assert_eq!(mesh.boundary_name_of_face(7920).unwrap(), "fixedWalls");
+        assert_eq!(mesh.boundary_name_of_face(11359).unwrap(), "frontAndBack");
+        assert!(mesh.boundary_name_of_face(0).is_none());
+        assert!(mesh.boundary_name_of_face(11360).is_none());
+        let id = mesh.face_neighbor(7840).unwrap();
+        let (name, b) = mesh.boundary_by_id(id).unwrap();
+        assert_eq!(name, "movingWall");
+        assert_eq!(b.start_face, 7840);
+        assert!(mesh.boundary_by_id(0).is_none());
+    }
+
+    #[test]
+    fn test_boundaries_ordered() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let names: Vec<&String> = mesh.boundaries_ordered()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["movingWall", "fixedWalls", "frontAndBack"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test");
+        let mut mesh = FoamMesh::new_validated(d.join("cavity")).unwrap();
+        mesh.faces[10][2] = 5043;
+        match mesh.validate() {
+            Err(FoamError::InvalidData(msg)) => assert_eq!(
+                msg,
+                "Face 10 references point 5043, but there are only 5043 \
+                points."
+            ),
+            _ => panic!("Expected an invalid face")
+        }
+
+        assert!(FoamMesh::new(d.join("corrupt_mesh")).is_ok());
+        match FoamMesh::new_validated(d.join("corrupt_mesh")) {
+            Err(FoamError::InvalidData(msg)) => assert!(
+                msg.starts_with("Face 3 references point 16"), "{}", msg),
+            _ => panic!("Expected an invalid face")
+        }
+        match FoamMesh::new(d.join("corrupt_boundary")) {
+            Err(FoamError::InvalidData(msg)) => assert!(
+                msg.contains("\"inlet\" consists of faces 14..18"), "{}", msg),
+            _ => panic!("Expected an invalid boundary")
+        }
+    }
+
+    #[test]
+    fn test_new_region() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/multi_region");
+        let fluid = FoamMesh::new_region(&d, "fluid").unwrap();
+        assert_eq!(fluid.region, "fluid");
+        assert_eq!(fluid.cell_faces.len(), 3);
+        assert_eq!(fluid.num_cells(), 3);
+        assert_eq!(
+            fluid.boundary["fluid_to_solid"].boundary_type,
+            "mappedWall"
+        );
+        let solid = FoamMesh::new_region(&d, "solid").unwrap();
+        assert_eq!(solid.cell_faces.len(), 2);
+        assert_relative_eq!(solid.points[0], Point3::new(3.0, 0.0, 0.0));
+        assert!(FoamMesh::new(&d).is_err());
+    }
+
+    #[test]
+    fn test_new_decomposed() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/decomposed");
+        let meshes = FoamMesh::new_decomposed(&d).unwrap();
+        assert_eq!(meshes.len(), 11);
+        for (i, mesh) in meshes.iter().enumerate() {
+            assert_eq!(mesh.path, d.join(format!("processor{}", i)));
+        }
+        assert_eq!(meshes[0].faces.len(), 11);
+        assert_eq!(meshes[0].num_inner_faces(), 1);
+        assert_eq!(meshes[10].faces.len(), 6);
+        assert!(meshes[10].boundary.contains_key("procBoundary10to9"));
+        assert_eq!(meshes[10].boundary["outlet"].start_face, 5);
+        assert!(FoamMesh::new_decomposed(d.join("processor0")).is_err());
+    }
+
+    #[test]
+    fn test_reconstruct_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let t = reconstruct_field(
+            d.join("resources/test/decomposed"),
+            "0.5",
+            "T",
+            |s| s.parse::<f64>().ok()
+        ).unwrap();
+        assert_eq!(t.len(), 12);
+        for (i, &v) in t.iter().enumerate() {
+            assert_relative_eq!(v, 0.5 * i as f64);
+        }
+        let err = reconstruct_field(
+            d.join("resources/test/decomposed_no_addressing"),
+            "0.5",
+            "T",
+            |s| s.parse::<f64>().ok()
+        ).unwrap_err();
+        assert!(err.to_string().contains("cellProcAddressing"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let json = serde_json::to_string(&mesh).unwrap();
+        let restored: FoamMesh = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.path, mesh.path);
+        assert_eq!(restored.faces[0], mesh.faces[0]);
+        assert_eq!(restored.faces[11359], mesh.faces[11359]);
+        assert_eq!(restored.boundary, mesh.boundary);
+        assert_eq!(restored.points[5042], mesh.points[5042]);
+        assert_eq!(restored.num_cells(), mesh.num_cells());
+        assert_eq!(restored.face_zones, mesh.face_zones);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_new_mmap() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let mapped = FoamMesh::new_mmap(&d).unwrap();
+        assert_eq!(mapped.points, mesh.points);
+        assert_eq!(mapped.faces, mesh.faces);
+        assert_eq!(mapped.owners, mesh.owners);
+        assert_eq!(mapped.neighbors, mesh.neighbors);
+        assert_eq!(mapped.cell_faces, mesh.cell_faces);
+        assert_eq!(mapped.boundary, mesh.boundary);
+        assert_eq!(mapped.num_cells(), mesh.num_cells());
+        // Compressed mesh files are mapped and decompressed as well:
+        let gz = FoamMesh::new_mmap(d.join("../cavity_gz")).unwrap();
+        assert_eq!(gz.points, mesh.points);
+        assert_eq!(gz.faces, mesh.faces);
+    }
+
+    #[test]
+    fn test_write_polymesh() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let out = std::env::temp_dir().join("openfoamparser_polymesh");
+        mesh.write_polymesh(out.join("constant/polyMesh")).unwrap();
+        let written = FoamMesh::new(&out).unwrap();
+        assert_eq!(written.points, mesh.points);
+        assert_eq!(written.faces, mesh.faces);
+        assert_eq!(written.owners, mesh.owners);
+        assert_eq!(written.neighbors, mesh.neighbors);
+        assert_eq!(written.boundary, mesh.boundary);
+        assert_eq!(written.num_inner_faces(), mesh.num_inner_faces());
+        assert_eq!(
+            parse_header(out.join("constant/polyMesh/owner")).unwrap().class,
+            "labelList"
+        );
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn test_write_vtk_legacy() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let out = std::env::temp_dir().join("openfoamparser_cavity.vtk");
+        mesh.write_vtk_legacy(&out).unwrap();
+        let vtk = std::fs::read_to_string(&out).unwrap();
+        assert!(vtk.starts_with("# vtk DataFile Version"));
+        assert!(vtk.contains("POINTS 5043 double\n"));
+        assert!(vtk.contains("POLYGONS 11360 56800\n"));
+        assert!(!vtk.contains("SCALARS p"));
+
+        let p = parse_scalar_field(d.join("0.5/p")).unwrap();
+        mesh.write_vtk_legacy_with_field(&out, "p", &p).unwrap();
+        let vtk = std::fs::read_to_string(&out).unwrap();
+        assert!(vtk.contains("SCALARS p double 1\n"));
+        assert!(mesh.write_vtk_legacy_with_field(&out, "p", &p[1..]).is_err());
+        std::fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn test_parse_field_timeseries() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let p = parse_field_timeseries(&d, "p", |s| s.parse::<f64>().ok())
+            .unwrap();
+        assert_eq!(p.len(), 2);
+        assert_eq!(p[0], (0.0, vec![0.0]));
+        assert_eq!(p[1].0, 0.5);
+        assert_eq!(p[1].1.len(), 3200);
+        // C only exists at 0.5:
+        let c = parse_field_timeseries(&d, "C", parse_point3::<f64>).unwrap();
+        assert_eq!(c.len(), 1);
+        assert_eq!(c[0].0, 0.5);
+    }
+
+    #[test]
+    fn test_write_internal_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity/0.5");
+        let flow: Vec<Vector3<f64>> = parse_internal_field(
+            d.join("U"), parse_vector3).unwrap();
+        let out = std::env::temp_dir().join("openfoamparser_U");
+        write_internal_field(
+            &out,
+            &flow,
+            "volVectorField",
+            "U",
+            &[0, 1, -1, 0, 0, 0, 0],
+            |u| format!("({} {} {})", u.x, u.y, u.z)
+        ).unwrap();
+        let written: Vec<Vector3<f64>> = parse_internal_field(
+            &out, parse_vector3).unwrap();
+        assert_eq!(written, flow);
+        let header = parse_header(&out).unwrap();
+        assert_eq!(header.class, "volVectorField");
+        assert_eq!(header.object, "U");
+        assert_eq!(
+            parse_field_dimensions(&out).unwrap(),
+            [0, 1, -1, 0, 0, 0, 0]
+        );
+        let content = std::fs::read_to_string(&out).unwrap();
+        assert!(content.contains("nonuniform List<vector>"));
+        std::fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let centers = vec![
+            Point3::new(0.0, 0.5, 1.0),
+            Point3::new(1.5, 0.5, 1.0),
+        ];
+        let out = std::env::temp_dir().join("openfoamparser_field.csv");
+        write_field_csv(
+            &centers,
+            &[Vector3::new(1.0, 2.0, 3.0), Vector3::new(-1.0, 0.0, 0.25)],
+            &out
+        ).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "cx,cy,cz,ux,uy,uz\n0,0.5,1,1,2,3\n1.5,0.5,1,-1,0,0.25\n"
+        );
+        write_scalar_csv(&centers, &[42.0, 1e-5], &out).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "cx,cy,cz,value\n0,0.5,1,42\n1.5,0.5,1,0.00001\n"
+        );
+        assert!(write_scalar_csv(&centers, &[42.0], &out).is_err());
+        std::fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn test_parse_cell_zones() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.cell_zones.len(), 2);
+        assert_eq!(m.cell_zones.get("lowerLeft").unwrap(), &vec![0, 1, 40, 41]);
+        assert_eq!(
+            m.cell_zones.get("lid").unwrap(),
+            &(1560..1600).collect::<Vec<usize>>()
+        );
+        // Meshes without zones yield an empty map:
+        let m = FoamMesh::new(d.join("resources/test/cavity_gz/")).unwrap();
+        assert!(m.cell_zones.is_empty());
+    }
+
+    #[test]
+    fn test_parse_face_zones() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.face_zones.len(), 1);
+        let zone = m.face_zones.get("midPlane").unwrap();
+        assert_eq!(zone.faces.len(), zone.flip_map.len());
+        assert_eq!(zone.faces.len(), 6);
+        assert_eq!(
+            zone.flip_map,
+            vec![false, false, false, true, true, true]
+        );
+    }
+
+    #[test]
+    fn test_load_sets() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let m = FoamMesh::new(&d).unwrap();
+        let sets = m.load_sets().unwrap();
+        assert_eq!(sets.len(), 2);
+
+        let (kind, cells) = &sets["lid"];
+        assert_eq!(*kind, SetKind::Cell);
+        let mut lid_cells = m.boundary_cells("movingWall");
+        lid_cells.sort_unstable();
+        assert_eq!(*cells, lid_cells);
+
+        let (kind, faces) = &sets["lidCorner"];
+        assert_eq!(*kind, SetKind::Face);
+        assert_eq!(*faces, vec![7840, 7841, 7842, 7843]);
+        assert_eq!(parse_set(d.join("constant/polyMesh/sets/lidCorner"))
+            .unwrap(), *faces);
+
+        let m = FoamMesh::new(PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/two_blocks")).unwrap();
+        assert!(m.load_sets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_point_zones() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert_eq!(m.point_zones.len(), 1);
+        assert_eq!(
+            m.point_zones.get("corners").unwrap(),
+            &vec![0, 40, 1640, 1680]
+        );
+        let m = FoamMesh::new(d.join("resources/test/cavity_gz/")).unwrap();
+        assert!(m.point_zones.is_empty());
+    }
+
+    #[test]
+    fn test_face_area_vector() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let centers = m.cell_centers.as_ref().unwrap();
+        // Cells are 0.0025 x 0.0025 x 0.005 in size:
+        for face_id in 0..m.num_inner_faces() {
+            let area = m.face_area(face_id).unwrap();
+            assert!(
+                relative_eq!(area, 0.0025 * 0.005)
+                    || relative_eq!(area, 0.0025 * 0.0025)
+            );
+            // Area vectors point from owner to neighbor:
+            let sf = m.face_area_vector(face_id).unwrap();
+            let d = centers[m.neighbors[face_id] as usize]
+                - centers[idx(m.owners[face_id])];
+            assert!(sf.dot(&d) > 0.0);
+        }
+        // frontAndBack faces point out of the domain in z direction:
+        let bd = m.boundary.get("frontAndBack").unwrap();
+        for face_id in bd.start_face .. bd.start_face + bd.num_faces {
+            let sf = m.face_area_vector(face_id).unwrap();
+            let z = m.points[idx(m.faces[face_id][0])].z;
+            let expected_z = if z > 0.0 { 1.0 } else { -1.0 };
+            assert_relative_eq!(
+                sf,
+                Vector3::new(0.0, 0.0, expected_z * 0.0025 * 0.0025)
+            );
+        }
+        assert_eq!(m.face_area_vector(m.faces.len()), None);
+    }
+
+    #[test]
+    fn test_face_normal() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mut mesh = FoamMesh::new(&d).unwrap();
+        mesh.compute_cell_centers();
+        let centers = mesh.cell_centers.as_ref().unwrap();
+        // First face of movingWall, i.e., the top of the cavity:
+        let face_id = mesh.boundary["movingWall"].start_face;
+        let normal = mesh.face_normal(face_id).unwrap();
+        assert_relative_eq!(normal, Vector3::new(0.0, 1.0, 0.0));
+        let (face_center, _) = mesh.face_center_and_area_vector(face_id)
+            .unwrap();
+        let owner_center = centers[idx(mesh.owners[face_id])];
+        assert!(normal.dot(&(face_center - owner_center)) > 0.0);
+        // Internal faces point towards the neighbor:
+        let normal = mesh.face_normal(0).unwrap();
+        assert_relative_eq!(normal.norm(), 1.0);
+        let owner_to_neighbor = centers[mesh.neighbors[0] as usize]
+            - centers[idx(mesh.owners[0])];
+        assert!(normal.dot(&owner_to_neighbor) > 0.0);
+        assert_eq!(mesh.face_normal(11360), None);
+    }
+
+    #[test]
+    fn test_non_orthogonality() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let angles = mesh.non_orthogonality();
+        assert_eq!(angles.len(), 7840);
+        let max = angles.iter().cloned().fold(0.0, f64::max);
+        assert!(max < 1e-6, "max. non-orthogonality: {}", max);
+    }
+
+    #[test]
+    fn test_interpolate_cell_to_point() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mut mesh = FoamMesh::new(&d).unwrap();
+        mesh.compute_cell_centers();
+        let values = mesh.interpolate_cell_to_point(&vec![4.2; 3200]);
+        assert_eq!(values.len(), 5043);
+        for v in values {
+            assert_relative_eq!(v, 4.2, epsilon = 1e-12);
+        }
+        // The interior point between cells 0, 1, 40, 41 (and the same
+        // cells of the second layer) is equidistant to all of them:
+        let mut field = vec![0.0; 3200];
+        for &cell_id in &[0, 1, 40, 41, 1600, 1601, 1640, 1641] {
+            field[cell_id] = cell_id as f64;
+        }
+        let values = mesh.interpolate_cell_to_point(&field);
+        assert_relative_eq!(values[1681 + 42], 820.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_grad_scalar() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity/");
+        let mut mesh = FoamMesh::new(&d).unwrap();
+        mesh.compute_cell_centers();
+        let volumes = mesh.cell_volumes();
+        assert_relative_eq!(volumes[0], 0.0025 * 0.0025 * 0.005);
+        let p: Vec<f64> = mesh.cell_centers.as_ref().unwrap()
+            .iter()
+            .map(|c| c.x)
+            .collect();
+        let grad = mesh.grad_scalar(&p);
+        assert_eq!(grad.len(), 3200);
+        for layer in 0..2 {
+            for j in 1..39 {
+                for i in 1..39 {
+                    let cell_id = layer * 1600 + j * 40 + i;
+                    assert_relative_eq!(
+                        grad[cell_id],
+                        Vector3::new(1.0, 0.0, 0.0),
+                        epsilon = 1e-9
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_patch_area() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        assert_relative_eq!(
+            mesh.patch_area("movingWall").unwrap(),
+            0.1 * 0.01,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            mesh.patch_area("frontAndBack").unwrap(),
+            2.0 * 0.1 * 0.1,
+            epsilon = 1e-12
+        );
+        assert!(mesh.patch_area("inlet").is_none());
+    }
+
+    #[test]
+    fn test_patch_flux() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let num_faces = mesh.faces.len();
+        let phi = vec![2.0; num_faces];
+        assert_relative_eq!(
+            mesh.patch_flux("movingWall", &phi).unwrap(),
+            160.0
+        );
+        assert!(mesh.patch_flux("movingWall", &phi[..7900]).is_none());
+        assert!(mesh.patch_flux("inlet", &phi).is_none());
+        let u = vec![Vector3::new(0.5, 3.0, 1.0); num_faces];
+        assert_relative_eq!(
+            mesh.patch_flux_vector("movingWall", &u).unwrap(),
+            3.0 * 0.1 * 0.01,
+            epsilon = 1e-12
+        );
+        // The fluxes through the front and the back cancel out:
+        assert_relative_eq!(
+            mesh.patch_flux_vector("frontAndBack", &u).unwrap(),
+            0.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_cell() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mut mesh = FoamMesh::new(&d).unwrap();
+        let cell = mesh.cell(0).unwrap();
+        assert_eq!(cell.id(), 0);
+        assert_eq!(cell.faces(), &mesh.cell_faces[0][..]);
+        assert_eq!(cell.neighbors().len(), 6);
+        assert_eq!(cell.points(), mesh.cell_points(0));
+        assert_relative_eq!(cell.volume(), 0.0025 * 0.0025 * 0.005);
+        assert_relative_eq!(
+            cell.center(),
+            Point3::new(0.00125, 0.00125, 0.0025),
+            epsilon = 1e-12
+        );
+        assert!(mesh.cell(3200).is_none());
+        assert_eq!(mesh.cells().count(), 3200);
+        let total: f64 = mesh.cells().map(|cell| cell.volume()).sum();
+        assert_relative_eq!(total, 0.1 * 0.1 * 0.01, epsilon = 1e-12);
+
+        mesh.read_cell_centers(d.join("0.5/C")).unwrap();
+        assert_relative_eq!(
+            mesh.cell(3199).unwrap().center(),
+            Point3::new(0.09875, 0.09875, 0.0075)
+        );
+    }
+
+    #[test]
+    fn test_face() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let (boundary, internal): (Vec<Face>, Vec<Face>) = mesh.faces_iter()
+            .partition(|face| face.is_boundary());
+        assert_eq!(internal.len(), 7840);
+        assert_eq!(boundary.len(), 11360 - 7840);
+        assert!(boundary.iter().all(|face| face.neighbor().is_none()));
+
+        let face = mesh.face(0).unwrap();
+        assert_eq!(face.points(), &mesh.faces[0][..]);
+        assert_eq!(face.owner(), 0);
+        assert_eq!(face.neighbor(), Some(1));
+        let face = mesh.face(7840).unwrap();
+        assert_eq!(face.id(), 7840);
+        assert!(face.is_boundary());
+        assert_relative_eq!(face.area().unwrap(), 0.0025 * 0.005);
+        assert_relative_eq!(
+            face.normal().unwrap(),
+            Vector3::new(0.0, 1.0, 0.0),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(face.center().unwrap()[1], 0.1);
+        assert!(mesh.face(11360).is_none());
+    }
+
+    #[test]
+    fn test_volume_integral() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let mesh = FoamMesh::new(&d).unwrap();
+        let total_volume: f64 = mesh.cell_volumes().iter().sum();
+        let field = vec![2.0; mesh.num_cells()];
+        assert_relative_eq!(
+            mesh.volume_integral(&field).unwrap(),
+            2.0 * total_volume
+        );
+        assert_relative_eq!(
+            mesh.volume_average(&field).unwrap(),
+            2.0,
+            epsilon = 1e-12
+        );
+        // Cells 0..1600 form the lower half of the domain:
+        let field: Vec<f64> = (0..mesh.num_cells())
+            .map(|i| if i < 1600 { 1.0 } else { 3.0 })
+            .collect();
+        assert_relative_eq!(
+            mesh.volume_average(&field).unwrap(),
+            2.0,
+            epsilon = 1e-12
+        );
+        assert!(mesh.volume_integral(&field[1..]).is_none());
+        assert!(mesh.volume_average(&[]).is_none());
+    }
+
+    #[test]
+    fn test_index_type() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("resources/test/cavity");
+        let mesh = FoamMesh::new(&path).unwrap();
+        let faces = FoamMesh::parse_faces(
+            path.join("constant/polyMesh/faces"), None).unwrap();
+        assert_eq!(mesh.faces.len(), faces.len());
+        for &face_id in &[0, 1, 7839, 7840, 11359] {
+            let face: Vec<usize> = mesh.faces[face_id].iter()
+                .map(|&p| idx(p))
+                .collect();
+            assert_eq!(face, faces[face_id]);
+        }
+        assert_eq!(mesh.face_owner(7840), Some(idx(mesh.owners[7840])));
+        assert_eq!(to_index(idx(Index::MAX)).unwrap(), Index::MAX);
+        #[cfg(feature = "u32-index")]
+        {
+            assert_eq!(std::mem::size_of::<Index>(), 4);
+            assert!(to_index(u32::MAX as usize + 1).is_err());
+        }
+    }
+
+    #[test]
+    fn test_classify_field_length() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("resources/test/cavity");
+        let mesh = FoamMesh::new(&path).unwrap();
+        let p = parse_field(path.join("0.5/p"), |s| s.parse::<f64>().ok())
+            .unwrap();
+        assert_eq!(mesh.classify_field_length(p.internal.len()),
+            FieldLocation::Cell);
+        assert_eq!(FieldLocation::from_class(&p.class), FieldLocation::Cell);
+
+        // The internal field of `phi` has a value per internal face:
+        let phi = parse_field(path.join("0.5/phi"), |s| s.parse::<f64>().ok())
+            .unwrap();
+        assert_eq!(phi.internal.len(), mesh.num_inner_faces());
+        assert_eq!(mesh.classify_field_length(phi.internal.len()),
+            FieldLocation::Face);
+        assert_eq!(FieldLocation::from_class(&phi.class),
+            FieldLocation::Face);
+        assert_eq!(mesh.classify_field_length(11360), FieldLocation::Face);
+        assert_eq!(mesh.classify_field_length(42), FieldLocation::Unknown);
+        assert_eq!(FieldLocation::from_class("dictionary"),
+            FieldLocation::Unknown);
+    }
+
+    #[test]
+    fn test_nearest_cell() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let centers = m.cell_centers.clone().unwrap();
+        let offset = Vector3::new(1e-4, -1e-4, 1e-4);
+        for &cell_id in &[0, 1, 41, 1234, 3199] {
+            assert_eq!(m.nearest_cell(&centers[cell_id]), Some(cell_id));
+            assert_eq!(m.nearest_cell(&(centers[cell_id] + offset)),
+                Some(cell_id));
+        }
+        // Points outside of the domain map to the nearest boundary cell:
+        assert_eq!(m.nearest_cell(&Point3::new(-1.0, -1.0, 0.005)), Some(0));
+
+        #[cfg(feature = "kdtree")]
+        {
+            m.build_cell_index().unwrap();
+            for (cell_id, center) in centers.iter().enumerate() {
+                assert_eq!(m.nearest_cell(center), Some(cell_id));
+            }
+            assert_eq!(m.nearest_cell(&Point3::new(-1.0, -1.0, 0.005)),
+                Some(0));
+        }
+    }
+
+    #[test]
+    fn test_probe() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let centers = m.cell_centers.clone().unwrap();
+        let linear = |p: &Point3<f64>| 2.0 * p.x - 3.0 * p.y;
+        let field: Vec<f64> = centers.iter().map(linear).collect();
+        let vector_field: Vec<Vector3<f64>> = field.iter()
+            .map(|&val| Vector3::new(val, 1.0, -val))
+            .collect();
+
+        // At a cell center, the cell's value is returned:
+        let center = centers[1234];
+        assert_relative_eq!(m.probe_scalar(&field, &center).unwrap(),
+            field[1234]);
+        // The field is constant in z, and the x and y neighbors are
+        // symmetric:
+        let p = center + Vector3::new(0.0, 0.0, 0.002);
+        assert_relative_eq!(m.probe_scalar(&field, &p).unwrap(),
+            linear(&p), epsilon = 1e-12);
+        assert_relative_eq!(m.probe_vector(&vector_field, &p).unwrap(),
+            Vector3::new(linear(&p), 1.0, -linear(&p)), epsilon = 1e-12);
+        // Elsewhere, inverse distance weighting is close to linear:
+        let p = Point3::new(0.0421, 0.0377, 0.005);
+        assert_relative_eq!(m.probe_scalar(&field, &p).unwrap(),
+            linear(&p), epsilon = 5e-3);
+    }
+
+    #[test]
+    fn test_sample_line() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let field: Vec<f64> = m.cell_centers.as_ref().unwrap()
+            .iter()
+            .map(|c| 4.0 * c.y)
+            .collect();
+        let start = Point3::new(0.05, 0.001, 0.005);
+        let end = Point3::new(0.05, 0.099, 0.005);
+        let samples = m.sample_line_scalar(&field, start, end, 50);
+        assert_eq!(samples.len(), 50);
+        assert_eq!(samples[0].0, start);
+        assert_relative_eq!(samples[49].0, end, epsilon = 1e-12);
+        for pair in samples.windows(2) {
+            assert!(pair[1].1 > pair[0].1);
+        }
+        for (p, value) in &samples[1..49] {
+            assert_relative_eq!(*value, 4.0 * p.y, epsilon = 5e-3);
+        }
+        assert_eq!(m.sample_line_scalar(&field, start, end, 1).len(), 1);
+        assert!(m.sample_line_scalar(&field, start, end, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cells_intersecting_plane() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let centers = m.cell_centers.as_ref().unwrap();
+        // The cavity has 40 x 40 x 2 cells with a height of 0.0025:
+        let cells = m.cells_intersecting_plane(
+            Point3::new(0.0, 0.051, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let expected: Vec<usize> = (0..centers.len())
+            .filter(|&cell_id| (centers[cell_id].y - 0.05125).abs() < 1e-9)
+            .collect();
+        assert_eq!(expected.len(), 80);
+        assert_eq!(cells, expected);
+        // A plane along the cell faces only touches cells:
+        assert!(m.cells_intersecting_plane(
+            Point3::new(0.0, 0.05, 0.0), Vector3::new(0.0, 1.0, 0.0)
+        ).is_empty());
+        // The normal's length and orientation do not matter:
+        assert_eq!(m.cells_intersecting_plane(
+            Point3::new(0.0, 0.0, 0.0025), Vector3::new(0.0, 0.0, -2.0)
+        ).len(), 1600);
+    }
+
+    #[test]
+    fn test_merge_points() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        assert!(m.find_duplicate_points(1e-6).is_empty());
+        let faces = m.faces.clone();
+        let num_points = m.points.len();
+
+        // Let face 42 reference a copy of its first point:
+        let p = idx(m.faces[42][0]);
+        m.points.push(m.points[p] + Vector3::new(1e-9, 0.0, -1e-9));
+        m.faces[42][0] = num_points as Index;
+        assert_eq!(m.find_duplicate_points(1e-6), vec![(p, num_points)]);
+        assert!(m.find_duplicate_points(1e-10).is_empty());
+
+        assert_eq!(m.merge_points(1e-6), 1);
+        assert_eq!(m.points.len(), num_points);
+        assert_eq!(m.faces, faces);
+        assert!(m.validate().is_ok());
+        assert_eq!(m.merge_points(1e-6), 0);
+
+        // Adjacent points are found with a tolerance above their distance:
+        let p0 = idx(m.faces[0][0]);
+        let p1 = idx(m.faces[0][1]);
+        let (first, second) = (p0.min(p1), p0.max(p1));
+        let spacing = (m.points[p0] - m.points[p1]).norm();
+        assert_eq!(m.find_duplicate_points(spacing * 1.01)
+            .iter()
+            .filter(|&&pair| pair == (first, second))
+            .count(), 1);
+    }
+
+    #[test]
+    fn test_reverse_cuthill_mckee() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let bandwidth = |m: &FoamMesh| (0..m.num_inner_faces())
+            .map(|face_id| m.neighbors[face_id] as usize
+                - idx(m.owners[face_id]))
+            .max()
+            .unwrap();
+        // The two layers of cells are 1600 cells apart:
+        assert_eq!(bandwidth(&m), 1600);
+
+        let perm = m.reverse_cuthill_mckee();
+        let mut sorted = perm.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..3200).collect::<Vec<usize>>());
+
+        let centers = m.cell_centers.clone().unwrap();
+        let volumes = m.cell_volumes();
+        m.apply_permutation(&perm).unwrap();
+        assert!(m.validate().is_ok());
+        assert!(bandwidth(&m) < 100);
+        assert_eq!(m.cell_centers.as_ref().unwrap(),
+            &permute_field(&centers, &perm));
+        m.compute_cell_centers();
+        for (computed, expected) in m.cell_centers.as_ref().unwrap()
+                .iter()
+                .zip(permute_field(&centers, &perm)) {
+            assert_relative_eq!(*computed, expected, epsilon = 1e-12);
+        }
+        for (computed, expected) in m.cell_volumes()
+                .iter()
+                .zip(permute_field(&volumes, &perm)) {
+            assert_relative_eq!(*computed, expected, epsilon = 1e-15);
+        }
+
+        assert!(m.apply_permutation(&[0, 0]).is_err());
+        assert!(m.apply_permutation(&[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_no_neighbour_file() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/no_neighbour");
+        let m = FoamMesh::new_validated(&d).unwrap();
+        assert_eq!(m.num_inner_faces(), 0);
+        assert_eq!(m.num_cells(), 1);
+        assert_eq!(m.faces.len(), 6);
+        assert_eq!(m.neighbors, vec![m.boundary["walls"].boundary_id; 6]);
+        assert_eq!(m.cell_faces[0].len(), 6);
+        assert_relative_eq!(m.cell_volumes()[0], 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_no_trailing_newline() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/no_trailing_newline");
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        // Without a final newline after `)`, and with `)` glued to the last
+        // value:
+        for name in &["points", "points_glued"] {
+            let points = FoamMesh::parse_points(d.join(name), None).unwrap();
+            assert_eq!(points.len(), 5, "{}", name);
+            assert_eq!(points[4], Point3::new(0.0, 0.0, 1.0));
+            let points = FoamMesh::parse_points_with_options(
+                d.join(name), strict).unwrap();
+            assert_eq!(points.len(), 5, "{}", name);
+        }
+        for name in &["owner", "owner_glued"] {
+            let owners: Vec<usize> =
+                FoamMesh::parse_scalars(d.join(name), None).unwrap();
+            assert_eq!(owners, vec![0, 1, 1, 2, 3, 5], "{}", name);
+        }
+        let faces = FoamMesh::parse_faces(d.join("faces_glued"), None)
+            .unwrap();
+        assert_eq!(faces.len(), 3);
+        assert_eq!(faces[2], vec![1, 2, 4]);
+
+        let p = parse_internal_field(d.join("p"), |s| s.parse::<f64>().ok())
+            .unwrap();
+        assert_eq!(p, vec![1.0, 2.0, 3.0]);
+        let mut p = Vec::new();
+        for_each_internal_field_value(
+            d.join("p"), |s| s.parse::<f64>().ok(), |val| p.push(val)
+        ).unwrap();
+        assert_eq!(p, vec![1.0, 2.0, 3.0]);
+        let patches = parse_boundary_field(
+            d.join("p"), |s| s.parse::<f64>().ok()).unwrap();
+        assert_eq!(patches["outlet"].value, Some(vec![0.5, 0.25]));
+        assert_eq!(patches["walls"].patch_type, "zeroGradient");
+        // The shorthand on the line after `value nonuniform`, followed by
+        // another patch:
+        let patches = parse_boundary_field(
+            d.join("T"), |s| s.parse::<f64>().ok()).unwrap();
+        assert_eq!(patches["heater"].value, Some(vec![1.5; 100]));
+        assert_eq!(patches["walls"].patch_type, "zeroGradient");
+        assert_eq!(patches["walls"].value, None);
+        let u = parse_internal_field(d.join("U"), parse_vector3::<f64>)
+            .unwrap();
+        assert_eq!(u, vec![Vector3::new(1.0, 2.0, 3.0),
+            Vector3::new(4.0, 5.0, 6.0)]);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/blank_lines");
+        let mesh_dir = d.join("constant/polyMesh");
+        let strict = ParseOptions { strict: true, skip: None };
+        let empty_line = |err: FoamError, expected_line: usize| match err {
+            FoamError::MalformedLine { line, reason, .. } => {
+                assert_eq!(line, expected_line);
+                assert_eq!(reason, EMPTY_LINE);
+            },
+            e => panic!("Unexpected error: {}", e)
+        };
+
+        // The empty lines are skipped by default:
+        let points = FoamMesh::parse_points_with_options(
+            mesh_dir.join("points"), ParseOptions::default()).unwrap();
+        assert_eq!(points.len(), 8);
+        let faces = FoamMesh::parse_faces_with_options(
+            mesh_dir.join("faces"), ParseOptions::default()).unwrap();
+        assert_eq!(faces.len(), 6);
+        let owners: Vec<usize> = FoamMesh::parse_scalars_with_options(
+            mesh_dir.join("owner"), ParseOptions::default()).unwrap();
+        assert_eq!(owners, vec![0; 6]);
+        let m = FoamMesh::new_with_options(&d, ParseOptions::default())
+            .unwrap();
+        assert_eq!(m.num_cells(), 1);
+
+        // In strict mode, they are errors:
+        empty_line(FoamMesh::parse_points_with_options(
+            mesh_dir.join("points"), strict).unwrap_err(), 23);
+        empty_line(FoamMesh::parse_faces_with_options(
+            mesh_dir.join("faces"), strict).unwrap_err(), 22);
+        empty_line(FoamMesh::parse_scalars_with_options::<_, usize>(
+            mesh_dir.join("owner"), strict).unwrap_err(), 25);
+        assert!(matches!(
+            FoamMesh::new_with_options(&d, strict),
+            Err(FoamError::MalformedLine { .. })
+        ));
+        // Strict mode accepts well-formed files, including faces spanning
+        // multiple lines:
+        FoamMesh::new_with_options(d.join("../no_neighbour"), strict)
+            .unwrap();
+        let faces = FoamMesh::parse_faces_with_options(
+            d.join("../wrapped_faces/faces"), strict).unwrap();
+        assert_eq!(faces.len(), 4);
+    }
+
+    #[test]
+    fn test_courant_numbers() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        // Unit cube: 0.1 s * 5 m/s * 6 m^2 / (2 * 1 m^3)
+        let m = FoamMesh::new(d.join("resources/test/no_neighbour")).unwrap();
+        let co = m.courant_numbers(&[Vector3::new(3.0, 4.0, 0.0)], 0.1)
+            .unwrap();
+        assert_eq!(co.len(), 1);
+        assert_relative_eq!(co[0], 1.5, epsilon = 1e-12);
+        assert_eq!(m.courant_numbers(&[], 0.1), None);
+
+        // The cavity's cells are boxes of 2.5 mm x 2.5 mm x 5 mm:
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        let u = vec![Vector3::new(1.0, 0.0, 0.0); m.num_cells()];
+        let co = m.courant_numbers(&u, 0.005).unwrap();
+        let (dx, dz) = (0.0025, 0.005);
+        let expected = 0.005 * (2.0 * dx * dx + 4.0 * dx * dz)
+            / (2.0 * dx * dx * dz);
+        for val in co {
+            assert_relative_eq!(val, expected, max_relative = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_wall_distance() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        let dist = m.wall_distance();
+        assert_eq!(dist.len(), m.num_cells());
+        // Cells are 2.5 mm wide, and all sides but front and back are
+        // walls. The cells of the bottom row, i.e., the first 40 cells of
+        // each layer, are half a cell away from the bottom wall:
+        for layer in 0..2 {
+            for d in &dist[layer * 1600..layer * 1600 + 40] {
+                assert_relative_eq!(*d, 0.00125, epsilon = 1e-9);
             }
         }
+        // The cell at the center of a layer is farthest from all walls:
+        let center_cell = 20 * 40 + 20;
+        assert_relative_eq!(dist[center_cell], 0.04875, epsilon = 1e-9);
+        assert_relative_eq!(
+            dist.iter().cloned().fold(0.0, f64::max),
+            0.04875,
+            epsilon = 1e-9
+        );
 
-        let content: Vec<String> = read_to_string(&filename)?
-            .split('\n')
-            .skip(skip)
-            .map(|l| String::from(l))
-            .collect(); // TODO: rewrite loop below for single pass
-        let mut bd: HashMap<String, Boundary> = HashMap::new();
-        let mut in_boundary_field = false;
-        let mut in_patch_field = false;
-        let mut current_patch: String = String::from("");
-        let mut current_type: String = String::from("");
-        let mut current_num_faces: usize = 0;
-        let mut current_start_face: usize = 0;
-        let mut bid: i64 = 0; // TODO: can this really be <0?
+        // Without walls, all distances are infinite:
+        let mut m = m;
+        for bd in m.boundary.values_mut() {
+            bd.boundary_type = String::from("patch");
+        }
+        assert!(m.wall_distance().iter().all(|dist| dist.is_infinite()));
+    }
 
-        let mut i: usize = 0;
-        loop {
-            if i > content.len() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Reached end of file unexpectedly. \
-                    Missing closing bracket?"
-                ));
-            }
-            let line = content[i].clone();
-            if !in_boundary_field {
-                if let Ok(_) = line.trim().parse::<i64>() {
-                    in_boundary_field = true;
-                    if content[i+1].starts_with('(') {
-                        i += 2;
-                        continue;
-                    } else if content[i+1].trim().is_empty()
-                            && content[i+2].starts_with('(') {
-                        i += 3;
-                        continue;
-                    } else {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            "Missing '(' after number of boundaries"
-                        ));
-                    }
-                }
-            }
-            if in_boundary_field {
-                if line.starts_with(')') { break; }
-                if in_patch_field {
-                    if line.trim() == "}" {
-                        in_patch_field = false;
-                        bd.insert(current_patch, Boundary{
-                            boundary_type: current_type.clone(),
-                            num_faces: current_num_faces,
-                            start_face: current_start_face,
-                            boundary_id: -10-bid // TODO: why? In Python impl, _set_boundary_faces, -10 seems to be default neighbor for boundaries…
-                        });
-                        bid += 1;
-                        current_patch = String::from("");
-                    } else if line.contains("nFaces") {
-                        current_num_faces = get_parsed_val(&line)?;
-                    } else if line.contains("startFace") {
-                        current_start_face = get_parsed_val(&line)?;
-                    } else if line.contains("type") {
-                        current_type = String::from(get_val(&line)?);
-                    }
-                } else { // not in_patch_field
-                    if line.trim().is_empty() {
-                        i += 1;
-                        continue;
-                    }
-                    current_patch = String::from(line.trim());
-                    if content[i+1].trim() == "{" {
-                        i += 2;
-                    } else if content[i+1].trim().is_empty()
-                            && content[i+2].trim() == "{" {
-                        i += 3;
-                    } else {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            "Missing '{' after boundary patch"
-                        ));
-                    }
-                    in_patch_field = true;
-                    continue;
-                }
-            }
-            i += 1;
+    #[test]
+    fn test_parse_surface_scalar_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/two_blocks");
+        let m = FoamMesh::new(&d).unwrap();
+        let phi = parse_surface_scalar_field(d.join("0/phi"), &m).unwrap();
+        assert_eq!(phi.len(), m.faces.len());
+        assert_eq!(phi[..3], [0.5, -0.25, 1e-3]);
+        assert!(phi[3..23].iter().all(|&val| val == 0.0));
+        assert_eq!(phi[23..], [-1.5, -2.5, 4.0, 4.0]);
+        assert_relative_eq!(
+            m.patch_flux("inlet", &phi).unwrap(), -4.0, epsilon = 1e-12);
+
+        // The faces of the empty patch are zero:
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        let m = FoamMesh::new(&d).unwrap();
+        let phi = parse_surface_scalar_field(d.join("0.5/phi"), &m).unwrap();
+        assert_eq!(phi.len(), 11360);
+        assert!(phi[7840..].iter().all(|&val| val == 0.0));
+        // The mesh does not match the field:
+        assert!(parse_surface_scalar_field(
+            d.join("0.5/phi"),
+            &FoamMesh::new(d.join("../two_blocks")).unwrap()
+        ).is_err());
+    }
+
+    #[test]
+    fn test_extract_patch() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        // 40 x 2 faces on a grid of 41 x 3 points at the top of the cavity:
+        let (points, faces) = m.extract_patch("movingWall").unwrap();
+        assert_eq!(faces.len(), 80);
+        assert_eq!(points.len(), 123);
+        assert!(points.iter().all(|p| p.y == 0.1));
+        for (face, global_face) in faces.iter().zip(&m.faces[7840..7920]) {
+            let global_points: Vec<Point3<f64>> = global_face.iter()
+                .map(|&p| m.points[idx(p)])
+                .collect();
+            let local_points: Vec<Point3<f64>> = face.iter()
+                .map(|&p| points[p])
+                .collect();
+            assert_eq!(local_points, global_points);
         }
+        assert!(m.extract_patch("nonexistent").is_none());
+    }
 
-        Ok(bd)
+    #[test]
+    fn test_write_stl() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        let out = std::env::temp_dir().join("openfoamparser_fixedWalls.stl");
+        m.write_stl("fixedWalls", &out).unwrap();
+        let stl = std::fs::read_to_string(&out).unwrap();
+        std::fs::remove_file(&out).unwrap();
+        assert!(stl.starts_with("solid fixedWalls\n"));
+        assert!(stl.ends_with("endsolid fixedWalls\n"));
+        // Each of the 240 quads is split into two triangles:
+        assert_eq!(stl.matches("facet normal").count(), 480);
+        assert_eq!(stl.matches("vertex").count(), 3 * 480);
+        // The normals of the left wall point out of the domain:
+        assert!(stl.contains("facet normal -1 0 0"));
+        assert!(m.write_stl("nonexistent", &out).is_err());
     }
-}
 
-/// Parse an internal field such as a cell centers file.
-///
-/// Expects a closure `parse_fn` to parse a single value to
-/// the desired type (e.g., "(0.1 0 3.3)" to a Vector3).
-///
-/// If the internal field is declared 'nonuniform', this function
-/// will only read the first section (for some reason), such as to be
-/// compatible with the reference Python implementation for now.
-///
-/// Similarly, if the internal field is declared 'uniform',
-/// only the first data line will be read.
-pub fn parse_internal_field<T, P, F>(
-    filename: P,
-    parse_fn: F
-) -> Result<Vec<T>, io::Error> where
-        P: AsRef<Path>,
-        F: Fn(&str) -> Option<T> {
-    let content: Vec<String> = read_to_string(&filename)?
-            .split('\n')
-            .map(|s| String::from(s))
-            .collect();
-    for (i, line) in content.iter().enumerate() {
-        if !line.starts_with("internalField") { continue; }
-        if line.contains("nonuniform") {
-            return parse_internal_field_data_nonuniform(
-                &content,
-                i,
-                content.len(),
-                parse_fn
+    #[test]
+    fn test_boundary_faces() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        assert_eq!(m.boundary_faces("fixedWalls"),
+            (7920..8160).collect::<Vec<usize>>());
+        for face_id in m.boundary_faces("movingWall") {
+            assert_eq!(m.boundary_name_of_face(face_id).unwrap(),
+                "movingWall");
+        }
+        assert!(m.boundary_faces("nonexistent").is_empty());
+    }
+
+    #[test]
+    fn test_boundary_cell_iter() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let m = FoamMesh::new(d.join("resources/test/cavity")).unwrap();
+        for bd_name in m.boundary.keys() {
+            assert_eq!(
+                m.boundary_cell_iter(bd_name).unwrap().collect::<Vec<_>>(),
+                m.boundary_cells(bd_name)
             );
-        } else if line.contains("uniform") {
-            return parse_internal_field_data_uniform(
-                line,
-                parse_fn
+        }
+        // The top row of cells in both layers:
+        assert_eq!(m.boundary_cell_iter("movingWall").unwrap().count(), 80);
+        assert_eq!(
+            m.boundary_cell_iter("movingWall").unwrap().min(),
+            Some(39 * 40)
+        );
+        assert!(m.boundary_cell_iter("nonexistent").is_none());
+        assert!(m.boundary_cells("nonexistent").is_empty());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        // With the `fast-float` feature, the results must not change:
+        for s in &[
+            "0", "-0.0", "1e-05", "1E-5", "+1.5", ".5", "5.", "0.1",
+            "2.2250738585072014e-308", "4.9e-324", "1.7976931348623157e308",
+            "1e400", "-1e-400", "123456789012345678901234567890",
+            "0.30000000000000004", "inf", "-inf", "nan", "NaN",
+            "", "-", "e5", "1e", "1.0.0", "0x10", "1_000", " 1", "(1",
+        ] {
+            let expected = s.parse::<f64>().ok();
+            let val = parse_value::<f64>(s);
+            assert_eq!(val.map(f64::is_nan), expected.map(f64::is_nan),
+                "{}", s);
+            if !expected.is_some_and(f64::is_nan) {
+                assert_eq!(val.map(f64::to_bits), expected.map(f64::to_bits),
+                    "{}", s);
+            }
+            assert_eq!(
+                parse_value::<f32>(s).map(f32::to_bits),
+                s.parse::<f32>().ok().map(f32::to_bits),
+                "{}", s
             );
         }
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Malformed internal field file: Not defined as either \
-            uniform of nonuniform."
-        ));
+        assert_eq!(parse_value::<i32>("-42"), Some(-42));
+        assert_eq!(parse_point3::<f64>("(1e-05 -0.0 3)"),
+            Some(Point3::new(1e-5, -0.0, 3.0)));
     }
-    Err(io::Error::new(
-        io::ErrorKind::InvalidData,
-        "Did not find any data in internal field file."
-    ))
-}
 
-/// Parse uniform data from a line.
-///
-/// Example input line:
-/// ```plaintext
-/// value           uniform (0 0 0);
-/// ```
-fn parse_internal_field_data_uniform<T, F>(
-    line: &str,
-    parse_fn: F
-) -> Result<Vec<T>, io::Error> where
-        F: Fn(&str) -> Option<T> {
-    let start = line.find('(');
-    let end = line.find(')');
-    if let (Some(start), Some(end)) = (start, end) {
-        Ok(line[start+1..end]
-             .split(' ')
-             .filter_map(|s| parse_fn(s))
-             .collect()
-        )
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!(
-                "Malformed internal field uniform data line:\n{}",
-                line
-            )
-        ))
+    #[test]
+    fn test_connected_components() {
+        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        d.push("resources/test/cavity");
+        let m = FoamMesh::new(&d).unwrap();
+        assert_eq!(m.connected_components(), vec![0; 3200]);
+        assert_eq!(m.num_components(), 1);
+
+        // Two rows of three and two cells, respectively:
+        d.pop();
+        d.push("two_blocks");
+        let m = FoamMesh::new_validated(&d).unwrap();
+        assert_eq!(m.connected_components(), vec![0, 0, 0, 1, 1]);
+        assert_eq!(m.num_components(), 2);
     }
-}
 
-fn parse_internal_field_data_nonuniform<T, F>(
-    content: &[String],
-    start: usize,
-    _end: usize, // only needed for binary, not implemented yet
-    parse_fn: F
-) -> Result<Vec<T>, io::Error> where
-        F: Fn(&str) -> Option<T> {
-    if let Ok(num_vals_expected) = content[start+1].parse::<usize>() {
-        if num_vals_expected + start > content.len() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Internal field file is shorter than declared."
-            ));
+    #[test]
+    fn test_compute_cell_centers() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
+        m.compute_cell_centers();
+        let computed = m.cell_centers.take().unwrap();
+        m.read_cell_centers(m.path.join("0.5/C")).unwrap();
+        let expected = m.cell_centers.unwrap();
+        assert_eq!(computed.len(), expected.len());
+        for (c, e) in computed.iter().zip(expected.iter()) {
+            assert_relative_eq!(c, e, epsilon = 1e-12);
         }
-        let mut data: Vec<T> = Vec::new();
-        data.reserve_exact(num_vals_expected);
-        for line in &content[start+3..start+3+num_vals_expected] {
-            if let Some(val) = parse_fn(line) {
-                data.push(val);
+    }
+
+    #[test]
+    fn test_scaled_cell_geometry() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        // Taper the cells, so that their centroids differ from the average
+        // of their face centers:
+        let tapered = |scale: f64| {
+            let mut m = FoamMesh::new(&d).unwrap();
+            for p in &mut m.points {
+                p.x *= 1.0 + 5.0 * p.y;
+                *p *= scale;
             }
+            m
+        };
+        let (centers, volumes) = tapered(1.0).cell_centers_and_volumes();
+        // Cells of less than 5 nm on a side, i.e., of about 1e-26 m^3:
+        let scale = 1e-6;
+        let scaled = tapered(scale);
+        let (scaled_centers, scaled_volumes) =
+            scaled.cell_centers_and_volumes();
+        assert_relative_eq!(scaled_volumes[0], 3.125e-26, max_relative = 0.1);
+        for (c, sc) in centers.iter().zip(&scaled_centers) {
+            assert_relative_eq!(c * scale, *sc, max_relative = 1e-9);
         }
-        if data.len() != num_vals_expected {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "{} values expected, but parsed {}.",
-                    num_vals_expected,
-                    data.len()
-                )
-            ));
+        for (v, sv) in volumes.iter().zip(&scaled_volumes) {
+            assert_relative_eq!(v * scale.powi(3), *sv, max_relative = 1e-9);
         }
-        Ok(data)
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Malformed internal field file: Number of expected \
-            values not given."
-        ))
     }
-}
 
-fn parse_vals_from_brackets<T: std::str::FromStr>(s: &str) -> Option<Vec<T>> {
-    Some(s.strip_prefix("(")?
-        .strip_suffix(")")?
-        .split(' ')
-        .filter_map(|s| s.parse::<T>().ok())
-        .collect())
-}
+    #[test]
+    fn test_scaled_green_gauss() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity");
+        // Cells of 2.5 nm x 2.5 nm x 5 nm:
+        let mut m = FoamMesh::new(&d).unwrap();
+        for p in &mut m.points {
+            *p *= 1e-6;
+        }
+        m.compute_cell_centers();
+        let centers = m.cell_centers.clone().unwrap();
+        let wall_cells: HashSet<usize> = m.boundary_cells("movingWall")
+            .into_iter()
+            .chain(m.boundary_cells("fixedWalls"))
+            .collect();
+        let interior: Vec<usize> = (0..m.num_cells())
+            .filter(|cell| !wall_cells.contains(cell))
+            .collect();
 
-pub fn parse_point3<T>(s: &str) -> Option<Point3<T>> where
-        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
-    let vals = parse_vals_from_brackets(s)?;
-    if vals.len() != 3 { return None; }
-    Some(Point3::new(vals[0], vals[1], vals[2]))
-}
+        let p: Vec<f64> = centers.iter().map(|c| c.x).collect();
+        let grad = m.grad_scalar(&p);
+        for &cell in &interior {
+            assert_relative_eq!(grad[cell], Vector3::new(1.0, 0.0, 0.0),
+                epsilon = 1e-6);
+        }
 
-pub fn parse_vector3<T>(s: &str) -> Option<Vector3<T>> where
-        T: std::fmt::Debug + Copy + PartialEq + std::str::FromStr + 'static {
-    let vals = parse_vals_from_brackets(s)?;
-    if vals.len() != 3 { return None; }
-    Some(Vector3::new(vals[0], vals[1], vals[2]))
-}
+        let u: Vec<Vector3<f64>> = centers.iter()
+            .map(|c| Vector3::new(c.x, 2.0 * c.y, 0.0))
+            .collect();
+        let div = m.divergence(&u);
+        for &cell in &interior {
+            assert_relative_eq!(div[cell], 3.0, epsilon = 1e-6);
+        }
 
-fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String, io::Error> {
-    match std::fs::read_to_string(&path) {
-        Err(e) => Err(io::Error::new(
-            e.kind(),
-            format!(
-                "Could not read \"{}\": {}",
-                path.as_ref().to_string_lossy(),
-                e.to_string()
-            )
-        )),
-        Ok(s) => Ok(s)
+        let u: Vec<Vector3<f64>> = centers.iter()
+            .map(|c| Vector3::new(-c.y, c.x, 0.0))
+            .collect();
+        let curl = m.curl(&u);
+        for &cell in &interior {
+            assert_relative_eq!(curl[cell], Vector3::new(0.0, 0.0, 2.0),
+                epsilon = 1e-6);
+        }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::PathBuf;
 
     #[test]
-    fn test_parse_boundary() {
-        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("resources/test/cavity/constant/polyMesh/boundary");
-        let boundaries: HashMap<String, Boundary> = FoamMesh::parse_boundary(
-            d,
-            10 // default skip…
+    fn test_parse_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let field: FoamField<Vector3<f64>> = parse_field(
+            d.join("resources/test/cavity/0.5/U"),
+            parse_vector3
         ).unwrap();
-        let bd_fixed_wall = boundaries.get("fixedWalls").unwrap();
-        assert_eq!(bd_fixed_wall.boundary_type, "wall");
-        assert_eq!(bd_fixed_wall.num_faces, 240);
-        assert_eq!(bd_fixed_wall.start_face, 7920);
+        assert_eq!(field.class, "volVectorField");
+        assert_eq!(field.dimensions, [0, 1, -1, 0, 0, 0, 0]);
+        assert_eq!(field.internal.len(), 3200);
+        assert_eq!(field.boundary.len(), 3);
+        assert_eq!(
+            field.boundary["movingWall"].value,
+            Some(vec![Vector3::new(1.0, 0.0, 0.0)])
+        );
     }
 
     #[test]
-    fn test_parse_points() {
-        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("resources/test/cavity/constant/polyMesh/points");
-        let points: Vec<Point3<f64>> = FoamMesh::parse_points(
-            d,
-            10 // default skip…
+    fn test_parse_boundary_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let patches = parse_boundary_field(
+            d.join("resources/test/cavity/0.5/U"),
+            parse_vector3::<f64>
         ).unwrap();
-        assert_relative_eq!(points[0], Point3::new(0_f64, 0_f64, 0_f64));
-        assert_relative_eq!(
-            points[5042],
-            Point3::new(0.1_f64, 0.1_f64, 0.01_f64)
+        assert_eq!(patches.len(), 3);
+        let moving_wall = patches.get("movingWall").unwrap();
+        assert_eq!(moving_wall.patch_type, "fixedValue");
+        assert_eq!(
+            moving_wall.value,
+            Some(vec![Vector3::new(1.0, 0.0, 0.0)])
+        );
+        assert_eq!(patches.get("fixedWalls").unwrap().patch_type, "noSlip");
+        assert_eq!(patches.get("fixedWalls").unwrap().value, None);
+        assert_eq!(patches.get("frontAndBack").unwrap().patch_type, "empty");
+
+        let patches = parse_boundary_field(
+            d.join("resources/test/cavity/0.5/C"),
+            parse_vector3::<f64>
+        ).unwrap();
+        let fixed_walls = patches.get("fixedWalls").unwrap();
+        assert_eq!(fixed_walls.patch_type, "calculated");
+        let value = fixed_walls.value.as_ref().unwrap();
+        assert_eq!(value.len(), 240);
+        assert_relative_eq!(value[0], Vector3::new(0.0, 0.00125, 0.0025));
+        let moving_wall = patches.get("movingWall").unwrap();
+        assert_eq!(moving_wall.value.as_ref().unwrap().len(), 80);
+    }
+
+    #[test]
+    fn test_parse_components() {
+        assert_eq!(parse_components::<1>("(42)"), Some([42.0]));
+        assert_eq!(parse_components::<3>("(1 2 3)"), Some([1.0, 2.0, 3.0]));
+        assert_eq!(
+            parse_components::<6>("(1 2 3 4 5 6)"),
+            Some([1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
         );
+        assert_eq!(
+            parse_components::<9>("(1 2 3 4 5 6 7 8 9)"),
+            Some([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0])
+        );
+        assert_eq!(parse_components::<3>("(1 2)"), None);
+        assert_eq!(parse_components::<3>("(1 2 3 4)"), None);
+        assert_eq!(parse_components::<3>("(1 x 3)"), None);
+        assert_eq!(parse_components::<1>("42"), None);
     }
 
     #[test]
-    fn test_parse_faces() {
-        let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        d.push("resources/test/cavity/constant/polyMesh/faces");
-        let faces: Vec<Vec<usize>> = FoamMesh::parse_faces(
-            d,
-            10 // default skip…
-        ).unwrap();
-        assert_eq!(faces[0], vec![1, 42, 1723, 1682]);
+    fn test_parse_2d() {
+        assert_eq!(parse_vector2("(1 2)"), Some(Vector2::new(1.0, 2.0)));
+        assert_eq!(parse_point2("( 1\t2 )"), Some(Point2::new(1, 2)));
+        assert_eq!(parse_vector2::<f64>("(1 2 0)"), None);
+        assert_eq!(parse_vector2::<f64>("(1)"), None);
+        assert_eq!(parse_point2::<f64>("(1 x 2)"), None);
     }
 
     #[test]
-    fn test_parse_scalars() {
+    fn test_parse_symm_tensor() {
+        assert_eq!(
+            parse_symm_tensor("(1 2 3 4 5 6.5)"),
+            Some(SymmTensor {
+                xx: 1.0, xy: 2.0, xz: 3.0, yy: 4.0, yz: 5.0, zz: 6.5
+            })
+        );
+        assert_eq!(parse_symm_tensor("(1 2 3 4 5)"), None);
+        assert_eq!(parse_symm_tensor("(1 2 3 4 5 6 7)"), None);
+    }
+
+    #[test]
+    fn test_parse_tensor() {
+        let t: Matrix3<f64> = parse_tensor("(1 2 3 4 5 6 7 8 9)").unwrap();
+        assert_relative_eq!(t, Matrix3::new(
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0
+        ));
+        assert_eq!(t[(0, 1)], 2.0);
+        assert_eq!(parse_tensor::<f64>("(1 2 3 4 5 6 7 8)"), None);
+    }
+
+    #[test]
+    fn test_parse_field_matrix() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity/0.5");
+        let u = parse_vector_field_matrix(d.join("U")).unwrap();
+        let flow: Vec<Vector3<f64>> = parse_internal_field(
+            d.join("U"), parse_vector3).unwrap();
+        assert_eq!(u.shape(), (3200, 3));
+        assert_eq!(u.row(1).transpose(), flow[1]);
+        assert_eq!(u[(3199, 0)], flow[3199].x);
+        let p = parse_scalar_field_vector(d.join("p")).unwrap();
+        assert_eq!(p.len(), 3200);
+        assert_relative_eq!(p[1], -0.00115706);
+    }
+
+    #[test]
+    fn test_parse_uniform_vector_field() {
+        // `internalField uniform (0 0 0);`
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/cavity/0/U");
+        let flow: Vec<Vector3<f64>> =
+            parse_internal_field(&path, parse_vector3).unwrap();
+        assert_eq!(flow, vec![Vector3::zeros()]);
+        let flow: Vec<Vector3<f64>> = parse_internal_field_reader(
+            std::fs::File::open(&path).unwrap(), parse_vector3).unwrap();
+        assert_eq!(flow, vec![Vector3::zeros()]);
+        assert_eq!(
+            for_each_internal_field_value(&path, parse_vector3::<f64>, |_| {})
+                .unwrap(),
+            1
+        );
+        let u = parse_vector_field_matrix(&path).unwrap();
+        assert_eq!(u.shape(), (1, 3));
+
+        // Scalar parse functions still get the components:
+        let parse_fn = |s: &str| s.parse::<f64>().ok();
+        assert_eq!(parse_internal_field(&path, parse_fn).unwrap(),
+            vec![0.0, 0.0, 0.0]);
+        assert!(parse_internal_field_data_uniform(
+            "internalField uniform (a b c);", parse_fn).is_err());
+    }
+
+    #[test]
+    fn test_parse_dimensions() {
+        assert_eq!(
+            parse_dimensions("dimensions      [0 2 -2 0 0 0 0];"),
+            Some([0, 2, -2, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            parse_dimensions("[1 -3 0 0 0]"),
+            Some([1, -3, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(parse_dimensions("[0 2 -2 0]"), None);
+        assert_eq!(parse_dimensions("[0 2 x 0 0 0 0]"), None);
+        assert_eq!(
+            parse_dimensioned_scalar("nu    [0 2 -1 0 0 0 0] 1e-05;"),
+            Some(DimensionedScalar {
+                name: String::from("nu"),
+                dimensions: [0, 2, -1, 0, 0, 0, 0],
+                value: 1e-5,
+            })
+        );
+        let nu = parse_dimensioned_scalar("nu nu [0 2 -1 0 0 0 0] 0.01;");
+        assert_eq!(nu.unwrap().value, 0.01);
+        assert_eq!(parse_dimensioned_scalar("nu 0.01;"), None);
         let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        let owners: Vec<usize> = FoamMesh::parse_scalars(
-            d.join("resources/test/cavity/constant/polyMesh/owner"),
-            10 // default skip…
-        ).unwrap();
-        assert_eq!(owners[0], 0);
-        assert_eq!(owners[11359], 3199);
+        assert_eq!(
+            parse_field_dimensions(d.join("resources/test/cavity/0.5/U"))
+                .unwrap(),
+            [0, 1, -1, 0, 0, 0, 0]
+        );
     }
 
     #[test]
-    fn test_new_mesh() {
+    fn test_parse_repeated_list() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/repeated");
+        let owners: Vec<usize> = FoamMesh::parse_scalars(d.join("owner"), None)
+            .unwrap();
+        assert_eq!(owners, vec![0; 5]);
+        let t = parse_scalar_field(d.join("T")).unwrap();
+        assert_eq!(t, vec![0.0; 5]);
+        let u: Vec<Vector3<f64>> = parse_internal_field(
+            d.join("U"), parse_vector3).unwrap();
+        assert_eq!(u, vec![Vector3::new(1.0, 2.0, 3.0); 5]);
+        let bf = parse_boundary_field(d.join("T"), |s| s.parse::<f64>().ok())
+            .unwrap();
+        assert_eq!(bf["walls"].value, Some(vec![1.5; 3]));
+        // The shorthand on the line after `value nonuniform`:
+        let bf = parse_boundary_field(d.join("U"), parse_vector3::<f64>)
+            .unwrap();
+        assert_eq!(bf.len(), 2);
+        assert_eq!(bf["inlet"].value,
+            Some(vec![Vector3::new(0.0, 0.0, 1.0); 2]));
+        assert_eq!(bf["walls"].patch_type, "noSlip");
+        let parse_fn = |s: &str| s.parse::<f64>().ok();
+        assert_eq!(parse_repeated_list("2{x}", parse_fn), None);
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/include/0");
+        let t: Vec<f64> = parse_scalar_field(d.join("T")).unwrap();
+        assert_eq!(t, vec![300.0, 310.0, 320.0]);
+        assert_eq!(
+            parse_field_dimensions(d.join("T")).unwrap(),
+            [0, 0, 0, 1, 0, 0, 0]
+        );
+        let bf = parse_boundary_field(
+            d.join("T"), |s| s.parse::<f64>().ok()).unwrap();
+        assert_eq!(bf["walls"].patch_type, "fixedValue");
+        assert_eq!(bf["walls"].value, Some(vec![300.0]));
+        match parse_scalar_field(d.join("cyclic")) {
+            Err(FoamError::InvalidData(msg)) => {
+                assert!(msg.contains("Cyclic include"), "{}", msg)
+            },
+            _ => panic!("Expected a cyclic include")
+        }
+    }
+
+    #[test]
+    fn test_parse_internal_field_malformed_line() {
         let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        let mut m = FoamMesh::new(d.join("resources/test/cavity/")).unwrap();
-        match m.read_cell_centers(m.path.join("0.5/C")) {
-            Err(e) => panic!("{:?}", e),
-            Ok(_) => {}
+        let res: Result<Vec<Vector3<f64>>, FoamError> = parse_internal_field(
+            d.join("resources/test/corrupt/U"),
+            parse_vector3
+        );
+        match res {
+            Err(e @ FoamError::MalformedLine { .. }) => {
+                let msg = e.to_string();
+                assert!(msg.contains("line 26 of"), "{}", msg);
+                assert!(msg.contains("(1 0 0 0)"), "{}", msg);
+            },
+            _ => panic!("Expected a malformed line")
+        }
+    }
+
+    #[test]
+    fn test_for_each_internal_field_value() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test");
+        let parse_f64 = |s: &str| s.parse::<f64>().ok();
+        for file in &["cavity/0.5/p", "cavity_gz/0.5/p", "multi_section/T",
+                "repeated/T"] {
+            let collected = parse_scalar_field(d.join(file)).unwrap();
+            let mut sum = 0.0;
+            let num_vals = for_each_internal_field_value(
+                d.join(file), parse_f64, |val| sum += val).unwrap();
+            assert_eq!(num_vals, collected.len(), "{}", file);
+            assert_relative_eq!(sum, collected.iter().sum::<f64>());
+        }
+        let mut max_x = f64::MIN;
+        let num_vals = for_each_internal_field_value(
+            d.join("binary/U"),
+            parse_vector3::<f64>,
+            |u| max_x = max_x.max(u.x)
+        ).unwrap();
+        assert_eq!(num_vals, 4);
+        assert!(max_x >= 1.0);
+        let res = for_each_internal_field_value(
+            d.join("corrupt/U"), parse_vector3::<f64>, |_| {});
+        assert!(matches!(
+            res,
+            Err(FoamError::MalformedLine { line: 26, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_internal_field_sections() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/test/multi_section/T");
+        let parse_fn = |s: &str| s.parse::<f64>().ok();
+        let t: Vec<f64> = parse_internal_field(&d, parse_fn).unwrap();
+        assert_eq!(t, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let t = parse_internal_field_sections(&d, parse_fn, false).unwrap();
+        assert_eq!(t, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_parse_uniform_whitespace() {
+        let parse_fn = |s: &str| s.parse::<f64>().ok();
+        let expected = vec![0.0, 1.5, 0.0];
+        for line in &[
+            "internalField   uniform (0 1.5 0);",
+            "internalField   uniform ( 0  1.5  0 );",
+            "internalField\tuniform (0\t1.5\t0);",
+            "internalField   uniform (\t0 \t1.5\t 0\t);",
+        ] {
+            assert_eq!(
+                parse_internal_field_data_uniform(line, parse_fn).unwrap(),
+                expected
+            );
+            assert_eq!(
+                parse_uniform_value(line, parse_vector3),
+                Some(Vector3::new(0.0, 1.5, 0.0))
+            );
         }
+    }
+
+    #[test]
+    fn test_field_reductions() {
+        let field = [2.0, -1.0, 4.0, 3.0];
+        assert_eq!(field_min(&field), Some(-1.0));
+        assert_eq!(field_max(&field), Some(4.0));
+        assert_eq!(field_mean(&field), Some(2.0));
+        assert_eq!(field_min(&[f64::NAN, 1.0]), Some(1.0));
+        assert_eq!(field_mean(&[]), None);
+
+        let field = [
+            Vector3::new(1.0, -2.0, 0.0),
+            Vector3::new(-3.0, 4.0, 2.0),
+            Vector3::new(2.0, 1.0, 1.0)
+        ];
+        assert_eq!(
+            field_min_vector(&field),
+            Some(Vector3::new(-3.0, -2.0, 0.0))
+        );
+        assert_eq!(
+            field_max_vector(&field),
+            Some(Vector3::new(2.0, 4.0, 2.0))
+        );
         assert_relative_eq!(
-            m.cell_centers.unwrap()[3199],
-            Point3::new(0.09875_f64, 0.09875_f64, 0.0075_f64)
+            field_mean_vector(&field).unwrap(),
+            Vector3::new(0.0, 1.0, 1.0)
         );
+        assert_relative_eq!(
+            field_magnitude_max(&field).unwrap(),
+            29_f64.sqrt()
+        );
+        assert_eq!(field_magnitude_max(&[]), None);
+    }
+
+    #[test]
+    fn test_parse_scalar_field() {
+        let d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let p = parse_scalar_field(
+            d.join("resources/test/cavity/0.5/p")
+        ).unwrap();
+        assert_eq!(p.len(), 3200);
+        assert_relative_eq!(p[0], 1.70129e-05);
+        assert_relative_eq!(p[1], -0.00115706);
+        let p = parse_scalar_field(
+            d.join("resources/test/cavity/0/p")
+        ).unwrap();
+        assert_eq!(p, vec![0.0]);
     }
 }